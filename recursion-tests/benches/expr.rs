@@ -1,6 +1,6 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use pprof::criterion::{Output, PProfProfiler};
-use recursion::{experimental::compact::Compact, ExpandableExt, CollapsibleExt};
+use recursion::{experimental::compact::Compact, CollapsibleExt, ExpandableExt, TraversalContext};
 use recursion_tests::expr::{
     eval::{eval_layer, naive_eval},
     naive::Expr,
@@ -45,6 +45,18 @@ fn bench_eval(criterion: &mut Criterion) {
             |b, expr| b.iter(|| expr.as_ref().collapse_frames(eval_layer)),
         );
 
+        group.bench_with_input(
+            BenchmarkId::new(
+                "fold stack_machine lazy with reused traversal context",
+                depth,
+            ),
+            &boxed_big_expr,
+            |b, expr| {
+                let mut ctx = TraversalContext::new();
+                b.iter(|| expr.as_ref().collapse_frames_with(&mut ctx, eval_layer))
+            },
+        );
+
         group.bench_with_input(
             BenchmarkId::new("fold stack_machine lazy with new GAT-based compact", depth),
             &boxed_big_compact,
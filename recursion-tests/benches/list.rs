@@ -1,6 +1,9 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use pprof::criterion::{Output, PProfProfiler};
-use recursion::{Collapsible, MappableFrame, CollapsibleExt};
+use recursion::{
+    BimappableFrame, Collapsible, CollapsibleExt, CollapsibleLinearExt, CollapsiblePayloadExt,
+    Expandable, LinearFrame, MappableFrame,
+};
 
 enum PartiallyApplied {}
 
@@ -21,6 +24,25 @@ impl<Elem> MappableFrame for ListFrame<Elem, PartiallyApplied> {
     }
 }
 
+impl<Elem> BimappableFrame for ListFrame<Elem, PartiallyApplied> {
+    type Payload = Elem;
+    type WithPayload<P> = ListFrame<P, PartiallyApplied>;
+
+    #[inline(always)]
+    fn bimap_frame<A, B, P>(
+        input: Self::Frame<A>,
+        mut payload: impl FnMut(Elem) -> P,
+        mut f: impl FnMut(A) -> B,
+    ) -> ListFrame<P, B> {
+        match input {
+            ListFrame::Cons(elem, next) => ListFrame::Cons(payload(elem), f(next)),
+            ListFrame::Nil => ListFrame::Nil,
+        }
+    }
+}
+
+impl<Elem> LinearFrame for ListFrame<Elem, PartiallyApplied> {}
+
 struct CollapsibleSlice<'a, Elem>(&'a [Elem]);
 
 impl<'a, Elem: 'a> Collapsible for CollapsibleSlice<'a, Elem> {
@@ -35,6 +57,25 @@ impl<'a, Elem: 'a> Collapsible for CollapsibleSlice<'a, Elem> {
     }
 }
 
+/// A list's elements in reverse order, as lists are built from the last element to the first
+struct RevVec<Elem>(Vec<Elem>);
+
+impl<Elem> Expandable for RevVec<Elem> {
+    type FrameToken = ListFrame<Elem, PartiallyApplied>;
+
+    #[inline(always)]
+    fn from_frame(val: <Self::FrameToken as MappableFrame>::Frame<Self>) -> Self {
+        match val {
+            ListFrame::Cons(elem, RevVec(mut elems)) => {
+                elems.push(elem);
+                RevVec(elems)
+            }
+            ListFrame::Nil => RevVec(Vec::new()),
+        }
+    }
+}
+
+#[allow(clippy::unnecessary_fold)] // the point is to compare against `Iterator::fold`
 fn bench_eval(criterion: &mut Criterion) {
     let mut bigvec = Vec::with_capacity(1024 * 1024);
     bigvec.resize(1024 * 1024, 1);
@@ -42,7 +83,7 @@ fn bench_eval(criterion: &mut Criterion) {
 
     let mut group = criterion.benchmark_group("sum_via_fold");
 
-    for input in test_cases.into_iter() {
+    for input in test_cases.iter() {
         group.bench_with_input(
             BenchmarkId::new("fold iter", input.len()),
             &input,
@@ -61,6 +102,54 @@ fn bench_eval(criterion: &mut Criterion) {
                 })
             },
         );
+
+        group.bench_with_input(
+            BenchmarkId::new("fold_frames_left", input.len()),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    CollapsibleSlice(&input[..]).fold_frames_left(0, |acc, frame| match frame {
+                        ListFrame::Cons(e, ()) => e + acc,
+                        ListFrame::Nil => acc,
+                    })
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("fold_frames_right", input.len()),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    CollapsibleSlice(&input[..]).fold_frames_right(|frame| match frame {
+                        ListFrame::Cons(e, acc) => e + acc,
+                        ListFrame::Nil => 0,
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = criterion.benchmark_group("map_payload");
+
+    for input in test_cases.into_iter() {
+        group.bench_with_input(
+            BenchmarkId::new("map iter", input.len()),
+            &input,
+            |b, input| b.iter(|| input.iter().rev().map(|x| x * 2).collect::<Vec<_>>()),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("map_payload", input.len()),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    let RevVec(doubled) = CollapsibleSlice(&input[..]).map_payload(|x| x * 2);
+                    doubled
+                })
+            },
+        );
     }
     group.finish();
 }
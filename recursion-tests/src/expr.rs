@@ -5,11 +5,14 @@ pub mod naive;
 use futures::FutureExt;
 use recursion::{
     experimental::frame::{AsyncMappableFrame, MappableFrameRef},
-    MappableFrame, PartiallyApplied,
+    DiffableFrame, FoldableFrame, Label, LabelledFrame, MappableFrame, PartiallyApplied,
+    TryMappableFrame, ZippableFrame,
 };
 
 /// Simple expression language with some operations on integers
-#[derive(Debug, Clone, Copy)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum ExprFrame<A> {
     Add(A, A),
     Sub(A, A),
@@ -31,6 +34,98 @@ impl MappableFrame for ExprFrame<PartiallyApplied> {
     }
 }
 
+impl TryMappableFrame for ExprFrame<PartiallyApplied> {
+    #[inline(always)]
+    fn try_map_frame<A, B, E>(
+        input: Self::Frame<A>,
+        mut f: impl FnMut(A) -> Result<B, E>,
+    ) -> Result<Self::Frame<B>, E> {
+        Ok(match input {
+            ExprFrame::Add(a, b) => {
+                let a = f(a)?;
+                ExprFrame::Add(a, f(b)?)
+            }
+            ExprFrame::Sub(a, b) => {
+                let a = f(a)?;
+                ExprFrame::Sub(a, f(b)?)
+            }
+            ExprFrame::Mul(a, b) => {
+                let a = f(a)?;
+                ExprFrame::Mul(a, f(b)?)
+            }
+            ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+        })
+    }
+}
+
+impl FoldableFrame for ExprFrame<PartiallyApplied> {
+    #[inline(always)]
+    fn fold_frame<A, Acc>(
+        input: &Self::Frame<A>,
+        init: Acc,
+        mut f: impl FnMut(Acc, &A) -> Acc,
+    ) -> Acc {
+        match input {
+            ExprFrame::Add(a, b) | ExprFrame::Sub(a, b) | ExprFrame::Mul(a, b) => {
+                let acc = f(init, a);
+                f(acc, b)
+            }
+            ExprFrame::LiteralInt(_) => init,
+        }
+    }
+}
+
+impl DiffableFrame for ExprFrame<PartiallyApplied> {
+    #[inline(always)]
+    fn same_head<A, B>(left: &Self::Frame<A>, right: &Self::Frame<B>) -> bool {
+        match (left, right) {
+            (ExprFrame::Add(..), ExprFrame::Add(..))
+            | (ExprFrame::Sub(..), ExprFrame::Sub(..))
+            | (ExprFrame::Mul(..), ExprFrame::Mul(..)) => true,
+            (ExprFrame::LiteralInt(x), ExprFrame::LiteralInt(y)) => x == y,
+            _ => false,
+        }
+    }
+}
+
+impl LabelledFrame for ExprFrame<PartiallyApplied> {
+    #[inline(always)]
+    fn label_children<A>(input: Self::Frame<A>) -> Self::Frame<(Label, A)> {
+        let (lhs, rhs) = (Label::Name("lhs"), Label::Name("rhs"));
+        match input {
+            ExprFrame::Add(a, b) => ExprFrame::Add((lhs, a), (rhs, b)),
+            ExprFrame::Sub(a, b) => ExprFrame::Sub((lhs, a), (rhs, b)),
+            ExprFrame::Mul(a, b) => ExprFrame::Mul((lhs, a), (rhs, b)),
+            ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+        }
+    }
+}
+
+// literals with different values are treated as a shape mismatch
+impl ZippableFrame for ExprFrame<PartiallyApplied> {
+    #[inline(always)]
+    fn zip_frames<A, B>(
+        left: Self::Frame<A>,
+        right: Self::Frame<B>,
+    ) -> Result<Self::Frame<(A, B)>, (Self::Frame<A>, Self::Frame<B>)> {
+        match (left, right) {
+            (ExprFrame::Add(a1, b1), ExprFrame::Add(a2, b2)) => {
+                Ok(ExprFrame::Add((a1, a2), (b1, b2)))
+            }
+            (ExprFrame::Sub(a1, b1), ExprFrame::Sub(a2, b2)) => {
+                Ok(ExprFrame::Sub((a1, a2), (b1, b2)))
+            }
+            (ExprFrame::Mul(a1, b1), ExprFrame::Mul(a2, b2)) => {
+                Ok(ExprFrame::Mul((a1, a2), (b1, b2)))
+            }
+            (ExprFrame::LiteralInt(x), ExprFrame::LiteralInt(y)) if x == y => {
+                Ok(ExprFrame::LiteralInt(x))
+            }
+            mismatch => Err(mismatch),
+        }
+    }
+}
+
 impl<A> ExprFrame<A> {
     async fn map_async<'a, B, E>(
         input: ExprFrame<A>,
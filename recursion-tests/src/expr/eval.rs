@@ -6,6 +6,7 @@ use crate::expr::naive::Expr;
 use futures::future::BoxFuture;
 #[cfg(test)]
 use proptest::proptest;
+use recursion::{Collapsible, Expandable, MappableFrame, PartiallyApplied};
 
 #[derive(Debug, Clone)]
 pub struct ValidInt(i64);
@@ -18,6 +19,45 @@ pub enum CompiledExpr<A> {
     LiteralInt(ValidInt),
 }
 
+impl MappableFrame for CompiledExpr<PartiallyApplied> {
+    type Frame<X> = CompiledExpr<X>;
+
+    #[inline(always)]
+    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        match input {
+            CompiledExpr::Add(a, b) => CompiledExpr::Add(f(a), f(b)),
+            CompiledExpr::Sub(a, b) => CompiledExpr::Sub(f(a), f(b)),
+            CompiledExpr::Mul(a, b) => CompiledExpr::Mul(f(a), f(b)),
+            CompiledExpr::LiteralInt(x) => CompiledExpr::LiteralInt(x),
+        }
+    }
+}
+
+/// An expression tree in which every literal has been validated by `compile`
+#[derive(Debug, Clone)]
+pub struct Compiled(Box<CompiledExpr<Compiled>>);
+
+impl Expandable for Compiled {
+    type FrameToken = CompiledExpr<PartiallyApplied>;
+
+    fn from_frame(val: <Self::FrameToken as MappableFrame>::Frame<Self>) -> Self {
+        Compiled(Box::new(val))
+    }
+}
+
+impl Collapsible for &Compiled {
+    type FrameToken = CompiledExpr<PartiallyApplied>;
+
+    fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+        match self.0.as_ref() {
+            CompiledExpr::Add(a, b) => CompiledExpr::Add(a, b),
+            CompiledExpr::Sub(a, b) => CompiledExpr::Sub(a, b),
+            CompiledExpr::Mul(a, b) => CompiledExpr::Mul(a, b),
+            CompiledExpr::LiteralInt(x) => CompiledExpr::LiteralInt(x.clone()),
+        }
+    }
+}
+
 type CompileError = &'static str;
 
 // only looks at literal case - add/sub/mul ops are always valid
@@ -76,6 +116,23 @@ pub fn naive_eval(expr: &Expr) -> i64 {
     }
 }
 
+#[test]
+fn compile_error_path() {
+    use recursion::{CollapsiblePathExt, Path};
+
+    // 1 + (2 * 100)
+    let lit = |x| Box::new(Expr::LiteralInt(x));
+    let expr = Expr::Add(lit(1), Box::new(Expr::Mul(lit(2), lit(100))));
+
+    let compiled = (&expr).try_collapse_frames_with_path(|path: &Path, frame| {
+        compile(frame)
+            .map(eval_compiled)
+            .map_err(|err| format!("{} at {}", err, path))
+    });
+
+    assert_eq!(Err("invalid literal at root.rhs.rhs".to_string()), compiled);
+}
+
 // generate a bunch of expression trees and evaluate them
 #[cfg(test)]
 proptest! {
@@ -109,4 +166,131 @@ proptest! {
         assert_eq!(Ok(simple), eval_gat_try);
     }
 
+    #[test]
+    fn expr_compile(expr in arb_expr()) {
+        use recursion::{CollapsibleConvertExt, CollapsibleExt};
+
+        let invalid = (&expr).collapse_frames(|frame| match frame {
+            ExprFrame::Add(a, b) | ExprFrame::Sub(a, b) | ExprFrame::Mul(a, b) => a || b,
+            ExprFrame::LiteralInt(x) => x > 99,
+        });
+
+        let compiled: Result<Compiled, CompileError> = (&expr).try_convert_frames(compile);
+        match compiled {
+            Ok(compiled) => {
+                assert!(!invalid);
+                assert_eq!(naive_eval(&expr), (&compiled).collapse_frames(eval_compiled));
+            }
+            Err(err) => {
+                assert!(invalid);
+                assert_eq!("invalid literal", err);
+            }
+        }
+
+        let converted: Expr = (&expr).convert_frames(|frame| frame);
+        assert_eq!(expr, converted);
+    }
+
+    #[test]
+    fn expr_algebras(expr in arb_expr()) {
+        use recursion::{algebra, try_algebra, Algebra, CollapsibleConvertExt, CollapsibleExt, CollapsibleMetricsExt, CollapsibleStepwiseExt, TraversalContext, TryAlgebra};
+
+        type Token = ExprFrame<PartiallyApplied>;
+
+        let simple = naive_eval(&expr);
+        let mut eval = algebra::<Token, i64>(eval_layer);
+        let nodes = algebra::<Token, usize>(|frame| match frame {
+            ExprFrame::Add(a, b) | ExprFrame::Sub(a, b) | ExprFrame::Mul(a, b) => 1 + a + b,
+            ExprFrame::LiteralInt(_) => 1,
+        });
+
+        // algebras can be reused by reference, or run together in one pass
+        assert_eq!(simple, (&expr).collapse_frames(&mut eval));
+        let (value, count) = (&expr).collapse_frames((&mut eval).zip(nodes));
+        assert_eq!(simple, value);
+        assert_eq!((&expr).node_count(), count);
+        assert_eq!(simple.to_string(), (&expr).collapse_frames((&mut eval).map(|value| value.to_string())));
+
+        // as do the context-reusing and stepwise traversals, which also apply `finish`
+        let mut ctx = TraversalContext::new();
+        let described = (&mut eval).map(|value| value.to_string());
+        assert_eq!(simple.to_string(), (&expr).collapse_frames_with(&mut ctx, described));
+        let described = (&mut eval).map(|value| value.to_string());
+        assert_eq!(simple.to_string(), (&expr).collapse_frames_stepwise(described).run());
+
+        // infallible algebras can be lifted to run alongside fallible ones
+        let compiled = try_algebra::<Token, Compiled, CompileError>(|frame| {
+            compile(frame).map(Compiled::from_frame)
+        });
+        let both = (&expr).try_collapse_frames(compiled.zip(eval.fallible()));
+        let expected: Result<Compiled, CompileError> = (&expr).try_convert_frames(compile);
+        match (both, expected) {
+            (Ok((compiled, value)), Ok(_)) => {
+                assert_eq!(simple, value);
+                assert_eq!(simple, (&compiled).collapse_frames(eval_compiled));
+            }
+            (Err(err), Err(expected)) => assert_eq!(expected, err),
+            (both, expected) => panic!("mismatch: {:?} vs {:?}", both.map(|(_, v)| v), expected.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn expr_eval_with_context(exprs in proptest::collection::vec(arb_expr(), 1..8)) {
+        use recursion::{CollapsibleExt, TraversalContext};
+
+        let mut ctx = TraversalContext::new();
+        for expr in exprs.iter() {
+            let simple = naive_eval(expr);
+            let eval_with_ctx = expr.collapse_frames_with(&mut ctx, eval_layer);
+            let eval_with_ctx_try: Result<i64, String> =
+                expr.try_collapse_frames_with(&mut ctx, |x| Ok(eval_layer(x)));
+
+            // a failed traversal must leave the context ready for reuse
+            let failed: Result<i64, &str> = expr.try_collapse_frames_with(&mut ctx, |_| Err("failed"));
+
+            assert_eq!(simple, eval_with_ctx);
+            assert_eq!(Ok(simple), eval_with_ctx_try);
+            assert_eq!(Err("failed"), failed);
+        }
+    }
+
+    #[test]
+    fn expr_eval_stepwise(expr in arb_expr(), budget in 1..16usize) {
+        use recursion::{CollapsibleStepwiseExt, Progress};
+
+        let simple = naive_eval(&expr);
+
+        let mut machine = (&expr).collapse_frames_stepwise(eval_layer);
+        let eval_stepwise = loop {
+            if let Progress::Done(out) = machine.run_for(budget) {
+                break out;
+            }
+        };
+
+        assert_eq!(simple, eval_stepwise);
+    }
+
+    #[test]
+    fn expr_eval_checkpoint(expr in arb_expr(), budget in 1..16usize) {
+        use recursion::{Checkpoint, CollapseMachine, Collapsible, CollapsibleStepwiseExt, PartiallyApplied, Progress};
+
+        type ExprCheckpoint = Checkpoint<ExprFrame<PartiallyApplied>, Expr, i64>;
+
+        let simple = naive_eval(&expr);
+
+        // round-trip the traversal state through json between each batch of steps
+        let mut machine = expr.collapse_frames_stepwise(eval_layer);
+        let eval_checkpointed = loop {
+            if let Progress::Done(out) = machine.run_for(budget) {
+                break out;
+            }
+            let persisted = serde_json::to_string(machine.checkpoint()).unwrap();
+            drop(machine);
+            let checkpoint: ExprCheckpoint = serde_json::from_str(&persisted).unwrap();
+            machine = CollapseMachine::resume(checkpoint, Expr::into_frame as fn(_) -> _, eval_layer);
+        };
+
+        assert_eq!(simple, eval_checkpointed);
+    }
+
 }
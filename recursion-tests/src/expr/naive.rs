@@ -1,11 +1,12 @@
 use crate::expr::*;
 use proptest::prelude::*;
 use recursion::{
-    experimental::recursive::collapse::CollapsibleAsync, Collapsible, Expandable, PartiallyApplied,
+    experimental::recursive::collapse::CollapsibleAsync, Collapsible, Expandable, Layout,
+    PartiallyApplied, TreePrinter,
 };
 
 /// simple naive representation of a recursive expression AST.
-#[derive(Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub enum Expr {
     Add(Box<Expr>, Box<Expr>),
     Sub(Box<Expr>, Box<Expr>),
@@ -13,7 +14,39 @@ pub enum Expr {
     LiteralInt(i64),
 }
 
-impl<'a> Collapsible for &'a Expr {
+recursion::impl_structural_traits!(Expr: Clone, PartialEq, Eq, PartialOrd, Ord, Hash);
+
+// derived Debug would recurse once per level of nesting
+impl std::fmt::Debug for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max_width = if f.alternate() { 80 } else { usize::MAX };
+        let layout_frame = |frame| match frame {
+            ExprFrame::Add(a, b) => Layout::tuple("Add", [a, b]),
+            ExprFrame::Sub(a, b) => Layout::tuple("Sub", [a, b]),
+            ExprFrame::Mul(a, b) => Layout::tuple("Mul", [a, b]),
+            ExprFrame::LiteralInt(x) => Layout::tuple(format!("LiteralInt({})", x), []),
+        };
+        TreePrinter::new()
+            .max_width(max_width)
+            .write(self, layout_frame, f)
+    }
+}
+
+impl Collapsible for &Expr {
+    type FrameToken = ExprFrame<PartiallyApplied>;
+
+    #[inline(always)]
+    fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+        match self {
+            Expr::Add(a, b) => ExprFrame::Add(a, b),
+            Expr::Sub(a, b) => ExprFrame::Sub(a, b),
+            Expr::Mul(a, b) => ExprFrame::Mul(a, b),
+            Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+        }
+    }
+}
+
+impl Collapsible for &mut Expr {
     type FrameToken = ExprFrame<PartiallyApplied>;
 
     #[inline(always)]
@@ -84,3 +117,687 @@ pub fn arb_expr() -> impl Strategy<Value = Expr> {
         },
     )
 }
+
+#[cfg(test)]
+fn naive_metrics(expr: &Expr) -> recursion::TreeMetrics {
+    let mut metrics = recursion::TreeMetrics::default();
+    fn go(expr: &Expr, depth: usize, metrics: &mut recursion::TreeMetrics) {
+        metrics.node_count += 1;
+        metrics.height = metrics.height.max(depth);
+        match expr {
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => {
+                *metrics.branching_histogram.entry(2).or_insert(0) += 1;
+                go(a, depth + 1, metrics);
+                go(b, depth + 1, metrics);
+            }
+            Expr::LiteralInt(_) => {
+                metrics.leaf_count += 1;
+                *metrics.branching_histogram.entry(0).or_insert(0) += 1;
+            }
+        }
+    }
+    go(expr, 0, &mut metrics);
+    metrics
+}
+
+#[cfg(test)]
+fn expr_doc(expr: &Expr) -> recursion::Doc {
+    use recursion::{CollapsibleExt, Doc};
+
+    expr.collapse_frames(|frame| {
+        let call = |name: &'static str, a: Doc, b: Doc| {
+            let args = (Doc::softline() + a + Doc::text(",") + Doc::line() + b).nest(4);
+            (Doc::text(name) + Doc::text("(") + args + Doc::softline() + Doc::text(")")).group()
+        };
+        match frame {
+            ExprFrame::Add(a, b) => call("add", a, b),
+            ExprFrame::Sub(a, b) => call("sub", a, b),
+            ExprFrame::Mul(a, b) => call("mul", a, b),
+            ExprFrame::LiteralInt(x) => Doc::text(x.to_string()),
+        }
+    })
+}
+
+#[cfg(test)]
+fn naive_eq(a: &Expr, b: &Expr) -> bool {
+    match (a, b) {
+        (Expr::Add(a1, b1), Expr::Add(a2, b2))
+        | (Expr::Sub(a1, b1), Expr::Sub(a2, b2))
+        | (Expr::Mul(a1, b1), Expr::Mul(a2, b2)) => naive_eq(a1, a2) && naive_eq(b1, b2),
+        (Expr::LiteralInt(x), Expr::LiteralInt(y)) => x == y,
+        _ => false,
+    }
+}
+
+#[test]
+fn doc_group_fits() {
+    use recursion::Doc;
+
+    let group =
+        |a: &'static str, b: &'static str| (Doc::text(a) + Doc::line() + Doc::text(b)).group();
+    // groups that follow are measured flat, so the first group breaks to leave room for the second
+    let doc = group("aaa", "bbb") + group("c", "dddd");
+    assert_eq!("aaa\nbbbc dddd", doc.render(10));
+    assert_eq!("aaa bbbc dddd", doc.render(13));
+
+    // line breaks after the group, in its broken enclosing group, end the measurement
+    let doc = (group("aaa", "bbb") + Doc::line() + Doc::text("cccccc")).group();
+    assert_eq!("aaa bbb\ncccccc", doc.render(10));
+}
+
+#[test]
+fn expr_deep() {
+    use recursion::ExpandableExt;
+
+    // a left-nested chain of additions, too deep for derived traits or drop glue
+    let deep = |depth: usize, x: i64| {
+        Expr::expand_frames(Some(depth), |seed| match seed {
+            Some(0) => ExprFrame::LiteralInt(x),
+            Some(n) => ExprFrame::Add(Some(n - 1), None),
+            None => ExprFrame::LiteralInt(1),
+        })
+    };
+
+    let a = deep(100_000, 0);
+    let (same, cloned, greater) = (deep(100_000, 0), a.clone(), deep(100_000, 1));
+    assert_eq!(a, same);
+    assert_eq!(a, cloned);
+    assert!(a < greater);
+    assert!(format!("{:?}", a).starts_with("Add(Add(Add("));
+    assert!(expr_doc(&a).render(usize::MAX).starts_with("add(add(add("));
+
+    // indentation grows with depth, so check a broken layout on a shallower tree
+    let rendered = expr_doc(&deep(1_000, 0)).render(80);
+    assert!(rendered.starts_with("add(\n    add(\n        add("));
+    assert!(rendered.ends_with(",\n    1\n)"));
+
+    let mut b = deep(100_000, 0);
+    recursion::for_each_node_mut(&mut b, |node| {
+        if let Expr::LiteralInt(x) = node {
+            *x += 1;
+        }
+    });
+    let incremented = Expr::expand_frames(Some(100_000), |seed| match seed {
+        Some(0) => ExprFrame::LiteralInt(1),
+        Some(n) => ExprFrame::Add(Some(n - 1), None),
+        None => ExprFrame::LiteralInt(2),
+    });
+    assert_eq!(incremented, b);
+
+    let folded = recursion::Rewriter::new(recursion::Strategy::Innermost)
+        .rule("fold", fold_literals)
+        .rewrite(deep(100_000, 0))
+        .unwrap();
+    assert_eq!(Expr::LiteralInt(100_000), folded.term);
+    assert_eq!(100_000, folded.trace.len());
+
+    for expr in [a, same, cloned, greater, b, incremented] {
+        recursion::drop_frames(expr);
+    }
+}
+
+#[test]
+fn drop_in_place_placeholders() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static PLACEHOLDERS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    enum Tree {
+        Node(Box<Tree>, Box<Tree>),
+        Leaf,
+    }
+
+    impl Collapsible for &mut Tree {
+        type FrameToken = ExprFrame<PartiallyApplied>;
+
+        fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+            match self {
+                Tree::Node(a, b) => ExprFrame::Add(a, b),
+                Tree::Leaf => ExprFrame::LiteralInt(0),
+            }
+        }
+    }
+
+    recursion::impl_drop_frames!(
+        Tree,
+        {
+            PLACEHOLDERS.with(|count| count.set(count.get() + 1));
+            Tree::Leaf
+        },
+        Tree::Leaf
+    );
+
+    // a left-nested chain, where each node has one internal child and one leaf
+    let mut tree = Tree::Leaf;
+    for _ in 0..100_000 {
+        tree = Tree::Node(Box::new(tree), Box::new(Tree::Leaf));
+    }
+    drop(tree);
+
+    // only internal children are replaced, and nodes dropped with leaf children create no placeholders
+    assert_eq!(99_999, PLACEHOLDERS.with(Cell::get));
+}
+
+#[cfg(test)]
+fn fold_literals(frame: ExprFrame<Expr>) -> Result<Expr, ExprFrame<Expr>> {
+    match frame {
+        ExprFrame::Add(Expr::LiteralInt(a), Expr::LiteralInt(b)) => {
+            Ok(Expr::LiteralInt(a.wrapping_add(b)))
+        }
+        ExprFrame::Sub(Expr::LiteralInt(a), Expr::LiteralInt(b)) => {
+            Ok(Expr::LiteralInt(a.wrapping_sub(b)))
+        }
+        ExprFrame::Mul(Expr::LiteralInt(a), Expr::LiteralInt(b)) => {
+            Ok(Expr::LiteralInt(a.wrapping_mul(b)))
+        }
+        frame => Err(frame),
+    }
+}
+
+#[cfg(test)]
+fn literal_in(
+    egraph: &recursion::EGraph<ExprFrame<PartiallyApplied>>,
+    id: recursion::Id,
+) -> Option<i64> {
+    egraph.nodes(id).find_map(|node| match node {
+        ExprFrame::LiteralInt(x) => Some(x),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+fn wrapping_eval(expr: &Expr) -> i64 {
+    match expr {
+        Expr::Add(a, b) => wrapping_eval(a).wrapping_add(wrapping_eval(b)),
+        Expr::Sub(a, b) => wrapping_eval(a).wrapping_sub(wrapping_eval(b)),
+        Expr::Mul(a, b) => wrapping_eval(a).wrapping_mul(wrapping_eval(b)),
+        Expr::LiteralInt(x) => *x,
+    }
+}
+
+#[cfg(test)]
+fn size() -> impl recursion::Algebra<ExprFrame<PartiallyApplied>, Carrier = usize, Out = usize> {
+    recursion::algebra(|frame| match frame {
+        ExprFrame::Add(a, b) | ExprFrame::Sub(a, b) | ExprFrame::Mul(a, b) => 1 + a + b,
+        ExprFrame::LiteralInt(_) => 1,
+    })
+}
+
+#[test]
+fn expr_egraph_limits() {
+    use recursion::{EGraph, Id, Saturator, StopReason, Term};
+
+    // 0 + (1 + (2 + ... 9))
+    let sum = (0..9).rev().fold(Expr::LiteralInt(9), |acc, x| {
+        Expr::Add(Box::new(Expr::LiteralInt(x)), Box::new(acc))
+    });
+
+    let commute = |_: &EGraph<_>, frame: &ExprFrame<Id>| match frame {
+        ExprFrame::Add(a, b) => Some(Term::node(ExprFrame::Add(Term::Class(*b), Term::Class(*a)))),
+        _ => None,
+    };
+    // a + (b + c) => (a + b) + c
+    let associate =
+        |egraph: &EGraph<ExprFrame<PartiallyApplied>>, frame: &ExprFrame<Id>| match frame {
+            ExprFrame::Add(a, bc) => egraph.nodes(*bc).find_map(|node| match node {
+                ExprFrame::Add(b, c) => Some(Term::node(ExprFrame::Add(
+                    Term::node(ExprFrame::Add(Term::Class(*a), Term::Class(b))),
+                    Term::Class(c),
+                ))),
+                _ => None,
+            }),
+            _ => None,
+        };
+
+    let mut egraph = EGraph::new();
+    let root = egraph.add_term(sum.clone());
+    let saturation = Saturator::new().rule("commute", commute).run(&mut egraph);
+    assert_eq!(StopReason::Saturated, saturation.stop_reason);
+    assert_eq!(2, saturation.iterations);
+    // (1 + (2 + ... 9)) + 0 is in the same e-class
+    let rest = match &sum {
+        Expr::Add(_, rest) => egraph.add_term(rest.as_ref()),
+        _ => unreachable!(),
+    };
+    let zero = egraph.add_term(Expr::LiteralInt(0));
+    assert_eq!(
+        Some(egraph.find(root)),
+        egraph.lookup(ExprFrame::Add(rest, zero))
+    );
+
+    let mut egraph = EGraph::new();
+    egraph.add_term(sum.clone());
+    let saturation = Saturator::new()
+        .rule("commute", commute)
+        .rule("associate", associate)
+        .node_limit(1_000)
+        .run(&mut egraph);
+    assert_eq!(StopReason::NodeLimit, saturation.stop_reason);
+    assert!(egraph.node_count() > 1_000);
+
+    let mut egraph = EGraph::new();
+    egraph.add_term(sum);
+    let saturation = Saturator::new()
+        .rule("commute", commute)
+        .rule("associate", associate)
+        .iteration_limit(2)
+        .run(&mut egraph);
+    assert_eq!(StopReason::IterationLimit, saturation.stop_reason);
+    assert_eq!(2, saturation.iterations);
+}
+
+#[test]
+fn expr_rewrite_limit() {
+    use recursion::{Rewriter, Strategy};
+
+    let lit = |x| Box::new(Expr::LiteralInt(x));
+    let expr = Expr::Add(lit(1), Box::new(Expr::Sub(lit(2), lit(3))));
+
+    // never reaches a fixpoint
+    let commute = |frame: ExprFrame<Expr>| match frame {
+        ExprFrame::Add(a, b) => Ok(Expr::Add(Box::new(b), Box::new(a))),
+        frame => Err(frame),
+    };
+    let err = Rewriter::new(Strategy::Innermost)
+        .rule("commute", commute)
+        .limit(10)
+        .rewrite(expr.clone())
+        .unwrap_err();
+    // the rewrite that exceeds the limit is kept, and no more rules are applied after it
+    assert_eq!(["commute"; 11], err.0.trace[..]);
+    assert_eq!(
+        Expr::Add(Box::new(Expr::Sub(lit(2), lit(3))), lit(1)),
+        err.0.term
+    );
+    assert_eq!("rewrite limit of 10 exceeded", err.to_string());
+
+    // reaching the limit exactly is not an error
+    let folded = Rewriter::new(Strategy::Outermost)
+        .rule("fold", fold_literals)
+        .limit(2)
+        .rewrite(expr)
+        .unwrap();
+    assert_eq!(Expr::LiteralInt(0), folded.term);
+}
+
+#[test]
+fn expr_rewrite_revisits_root_only() {
+    use recursion::{CollapsibleMetricsExt, ExpandableExt, Rewriter, Strategy};
+    use std::cell::Cell;
+
+    // a chain of subtractions, multiplied by one many times over
+    let expr = Expr::expand_frames(Some((1_000, 1_000)), |seed| match seed {
+        Some((0, 0)) => ExprFrame::LiteralInt(0),
+        Some((0, subs)) => ExprFrame::Sub(Some((0, subs - 1)), Some((0, 0))),
+        Some((muls, subs)) => ExprFrame::Mul(Some((muls - 1, subs)), None),
+        None => ExprFrame::LiteralInt(1),
+    });
+    let nodes = (&expr).node_count();
+
+    let calls = Cell::new(0);
+    let rewritten = Rewriter::new(Strategy::Innermost)
+        .rule("times one", |frame: ExprFrame<Expr>| {
+            calls.set(calls.get() + 1);
+            match frame {
+                ExprFrame::Mul(a, Expr::LiteralInt(1)) => Ok(a),
+                frame => Err(frame),
+            }
+        })
+        .rewrite(expr)
+        .unwrap();
+    assert_eq!(1_000, rewritten.trace.len());
+    // once per node, and once more for the root of each result, rather than once per node of each result
+    assert_eq!(nodes + rewritten.trace.len(), calls.get());
+}
+
+#[test]
+fn expr_debug() {
+    let lit = |x| Box::new(Expr::LiteralInt(x));
+    let expr = Expr::Mul(Box::new(Expr::Add(lit(1), lit(2))), lit(3));
+
+    assert_eq!(
+        "Mul(Add(LiteralInt(1), LiteralInt(2)), LiteralInt(3))",
+        format!("{:?}", expr)
+    );
+
+    let wide = Expr::Sub(Box::new(expr.clone()), Box::new(expr));
+    assert_eq!(
+        "Sub(\n    Mul(Add(LiteralInt(1), LiteralInt(2)), LiteralInt(3)),\n    Mul(Add(LiteralInt(1), LiteralInt(2)), LiteralInt(3))\n)",
+        format!("{:#?}", wide)
+    );
+}
+
+#[test]
+fn expr_edit_distance_examples() {
+    use recursion::experimental::edit_distance::{tree_edit_distance, UnitCosts};
+
+    let lit = |x| Box::new(Expr::LiteralInt(x));
+    let add = Expr::Add(lit(1), lit(2));
+
+    assert_eq!(Some(0), tree_edit_distance(&add, &add, &UnitCosts));
+    // relabel a single literal
+    assert_eq!(
+        Some(1),
+        tree_edit_distance(&add, &Expr::Add(lit(1), lit(3)), &UnitCosts)
+    );
+    // relabel the root
+    assert_eq!(
+        Some(1),
+        tree_edit_distance(&add, &Expr::Mul(lit(1), lit(2)), &UnitCosts)
+    );
+    // delete the root and one literal
+    assert_eq!(
+        Some(2),
+        tree_edit_distance(&add, &Expr::LiteralInt(1), &UnitCosts)
+    );
+    // insert a new root and literal, keeping the original tree as a subtree
+    let nested = Expr::Sub(Box::new(add.clone()), lit(4));
+    assert_eq!(Some(2), tree_edit_distance(&add, &nested, &UnitCosts));
+}
+
+#[test]
+fn expr_edit_distance_large() {
+    use recursion::experimental::edit_distance::{tree_edit_distance, UnitCosts};
+    use recursion::ExpandableExt;
+
+    // the distance table is 'n * m' entries, so this is only feasible with one small tree
+    let balanced = |depth: usize| {
+        Expr::expand_frames(depth, |depth| match depth {
+            0 => ExprFrame::LiteralInt(1),
+            n => ExprFrame::Add(n - 1, n - 1),
+        })
+    };
+    let (large, small) = (balanced(16), balanced(2));
+    assert_eq!(
+        Some((1 << 17) - 1 - 7),
+        tree_edit_distance(&large, &small, &UnitCosts)
+    );
+    // two trees of four million nodes would need a table larger than the address space
+    let huge = balanced(21);
+    assert_eq!(None, tree_edit_distance(&huge, &huge, &UnitCosts));
+}
+
+#[cfg(test)]
+proptest! {
+    #[test]
+    fn expr_metrics(expr in arb_expr()) {
+        use recursion::CollapsibleMetricsExt;
+
+        let expected = naive_metrics(&expr);
+
+        assert_eq!(expected.node_count, (&expr).node_count());
+        assert_eq!(expected.leaf_count, (&expr).leaf_count());
+        assert_eq!(expected.height, (&expr).height());
+        assert_eq!(expected.branching_histogram, (&expr).branching_histogram());
+        assert_eq!(expected, (&expr).tree_metrics());
+    }
+
+    #[test]
+    fn expr_zip_eq(left in arb_expr(), right in arb_expr()) {
+        fn zip_eq(left: &Expr, right: &Expr) -> bool {
+            recursion::zip_collapse(
+                left,
+                right,
+                |frame| match frame {
+                    ExprFrame::Add(a, b) | ExprFrame::Sub(a, b) | ExprFrame::Mul(a, b) => a && b,
+                    ExprFrame::LiteralInt(_) => true,
+                },
+                |_, _| false,
+            )
+        }
+
+        assert!(zip_eq(&left, &left.clone()));
+        assert_eq!(naive_eq(&left, &right), zip_eq(&left, &right));
+    }
+
+    #[test]
+    fn expr_diff_apply(source in arb_expr(), target in arb_expr()) {
+        assert!(recursion::diff(&source, &source).is_unchanged());
+
+        let diff = recursion::diff(&source, &target);
+        assert_eq!(naive_eq(&source, &target), diff.is_unchanged());
+
+        let patched: Expr = diff.apply(&source).expect("diff applies to its own source");
+        assert!(naive_eq(&patched, &target));
+    }
+
+    #[test]
+    fn expr_edit_distance(a in arb_expr(), b in arb_expr(), c in arb_expr()) {
+        use recursion::experimental::edit_distance::{tree_edit_distance, UnitCosts};
+        use recursion::CollapsibleMetricsExt;
+
+        let dist = |x: &Expr, y: &Expr| tree_edit_distance(x, y, &UnitCosts).unwrap();
+        let (a_size, b_size) = ((&a).node_count() as u64, (&b).node_count() as u64);
+
+        assert_eq!(0, dist(&a, &a));
+        assert_eq!(dist(&a, &b), dist(&b, &a));
+        assert!(dist(&a, &b) >= a_size.abs_diff(b_size));
+        assert!(dist(&a, &b) <= a_size + b_size);
+        assert!(dist(&a, &c) <= dist(&a, &b) + dist(&b, &c));
+    }
+
+    #[test]
+    fn expr_structural_traits(a in arb_expr(), b in arb_expr()) {
+        use std::hash::{BuildHasher, RandomState};
+
+        let hasher = RandomState::new();
+
+        assert_eq!(a, a.clone());
+        assert_eq!(naive_eq(&a, &b), a == b);
+        assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+        assert_eq!(a == b, a.cmp(&b).is_eq());
+        assert_eq!(hasher.hash_one(&a), hasher.hash_one(a.clone()));
+    }
+
+    #[test]
+    fn expr_doc_render(expr in arb_expr(), width in 0..60usize) {
+        let doc = expr_doc(&expr);
+        let flat = doc.render(usize::MAX);
+        let rendered = doc.render(width);
+
+        assert!(!flat.contains('\n'));
+        // only whitespace differs between layouts
+        assert_eq!(
+            flat.replace(", ", ","),
+            rendered.split_whitespace().collect::<String>()
+        );
+        // every line fits, unless it can't be broken any further
+        for line in rendered.lines() {
+            assert!(line.len() <= width || !line.trim_end_matches(['(', ',', ')']).contains(['(', ',']));
+        }
+    }
+
+    #[test]
+    fn expr_paths(expr in arb_expr()) {
+        use recursion::{CollapsibleMetricsExt, CollapsiblePathExt, Label, Positional};
+
+        let mut labelled = Vec::new();
+        (&expr).collapse_frames_with_path(|path, _| labelled.push(path.labels()));
+        let mut positional = Vec::new();
+        Positional(&expr).collapse_frames_with_path(|path, _| positional.push(path.labels()));
+
+        // named labels correspond to child indices
+        let by_index: Vec<Vec<Label>> = labelled
+            .iter()
+            .map(|labels| {
+                labels
+                    .iter()
+                    .map(|label| match label {
+                        Label::Name("lhs") => Label::Index(0),
+                        Label::Name("rhs") => Label::Index(1),
+                        other => *other,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(by_index, positional);
+
+        // each node has a distinct path, with the root collapsed last
+        let mut distinct = labelled.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), (&expr).node_count());
+        assert_eq!(labelled.last(), Some(&Vec::new()));
+    }
+
+    #[test]
+    fn expr_fold_map(expr in arb_expr()) {
+        use recursion::{CollapsibleMetricsExt, CollapsibleMonoidExt, Max, Min, Monoid, Product, Sum};
+        use std::collections::{BTreeSet, HashSet};
+
+        fn literals(expr: &Expr, out: &mut Vec<i64>) {
+            match expr {
+                Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => {
+                    literals(a, out);
+                    literals(b, out);
+                }
+                Expr::LiteralInt(x) => out.push(*x),
+            }
+        }
+
+        let mut expected = Vec::new();
+        literals(&expr, &mut expected);
+
+        let (found, (Sum(count), Product(signs))): (Vec<i64>, (Sum<usize>, Product<i64>)) =
+            (&expr).fold_map_frames(|frame| match frame {
+                ExprFrame::LiteralInt(x) => (vec![x], (Sum(1), Product(x.signum()))),
+                _ => (Vec::empty(), (Sum(1), Product(1))),
+            });
+        assert_eq!(expected, found);
+        assert_eq!((&expr).node_count(), count);
+        assert_eq!(expected.iter().map(|x| x.signum()).product::<i64>(), signs);
+
+        let (Min(min), Max(max)) = (&expr).fold_map_frames(|frame| match frame {
+            ExprFrame::LiteralInt(x) => (Min(Some(x)), Max(Some(x))),
+            _ => Monoid::empty(),
+        });
+        assert_eq!(expected.iter().min().copied(), min);
+        assert_eq!(expected.iter().max().copied(), max);
+
+        let distinct: BTreeSet<i64> = (&expr).fold_map_frames(|frame| match frame {
+            ExprFrame::LiteralInt(x) => BTreeSet::from([x]),
+            _ => BTreeSet::new(),
+        });
+        let hashed: HashSet<i64> = (&expr).fold_map_frames(|frame| match frame {
+            ExprFrame::LiteralInt(x) => HashSet::from([x]),
+            _ => HashSet::new(),
+        });
+        assert_eq!(expected.iter().copied().collect::<BTreeSet<_>>(), distinct);
+        assert_eq!(expected.iter().copied().collect::<HashSet<_>>(), hashed);
+    }
+
+    #[test]
+    fn expr_for_each_node_mut(expr in arb_expr()) {
+        fn naive_abs(expr: &Expr) -> Expr {
+            match expr {
+                Expr::Add(a, b) => Expr::Add(Box::new(naive_abs(a)), Box::new(naive_abs(b))),
+                Expr::Sub(a, b) => Expr::Add(Box::new(naive_abs(a)), Box::new(naive_abs(b))),
+                Expr::Mul(a, b) => Expr::Mul(Box::new(naive_abs(a)), Box::new(naive_abs(b))),
+                Expr::LiteralInt(x) => Expr::LiteralInt(x.abs()),
+            }
+        }
+
+        // rewriting a node before its children are visited
+        let mut updated = expr.clone();
+        recursion::for_each_node_mut(&mut updated, |node| match node {
+            Expr::Sub(a, b) => {
+                let (a, b) = (std::mem::replace(a, Box::new(Expr::LiteralInt(0))), std::mem::replace(b, Box::new(Expr::LiteralInt(0))));
+                *node = Expr::Add(a, b);
+            }
+            Expr::LiteralInt(x) => *x = x.abs(),
+            _ => {}
+        });
+
+        assert_eq!(naive_abs(&expr), updated);
+    }
+
+    #[test]
+    fn expr_rewrite(expr in arb_expr()) {
+        use recursion::{Rewriter, Strategy};
+
+        fn count_ops(expr: &Expr) -> usize {
+            match expr {
+                Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => 1 + count_ops(a) + count_ops(b),
+                Expr::LiteralInt(_) => 0,
+            }
+        }
+
+        // folding literals fully evaluates the expression under every strategy but top-down,
+        // with one rewrite per operation
+        for strategy in [Strategy::BottomUp, Strategy::Innermost, Strategy::Outermost] {
+            let folded = Rewriter::new(strategy).rule("fold", fold_literals).rewrite(expr.clone()).unwrap();
+            assert_eq!(Expr::LiteralInt(wrapping_eval(&expr)), folded.term);
+            assert_eq!(count_ops(&expr), folded.trace.len());
+        }
+
+        // top-down visits the children of a rule's result, so this replaces every subtraction
+        fn naive_negate(expr: &Expr) -> Expr {
+            match expr {
+                Expr::Add(a, b) => Expr::Add(Box::new(naive_negate(a)), Box::new(naive_negate(b))),
+                Expr::Sub(a, b) => Expr::Add(
+                    Box::new(naive_negate(a)),
+                    Box::new(Expr::Mul(Box::new(Expr::LiteralInt(-1)), Box::new(naive_negate(b)))),
+                ),
+                Expr::Mul(a, b) => Expr::Mul(Box::new(naive_negate(a)), Box::new(naive_negate(b))),
+                Expr::LiteralInt(x) => Expr::LiteralInt(*x),
+            }
+        }
+        let negated = Rewriter::new(Strategy::TopDown)
+            .rule("negate", |frame: ExprFrame<Expr>| match frame {
+                ExprFrame::Sub(a, b) => Ok(Expr::Add(
+                    Box::new(a),
+                    Box::new(Expr::Mul(Box::new(Expr::LiteralInt(-1)), Box::new(b))),
+                )),
+                frame => Err(frame),
+            })
+            .rewrite(expr.clone())
+            .unwrap();
+        assert_eq!(naive_negate(&expr), negated.term);
+    }
+
+    #[test]
+    fn expr_egraph(expr in arb_expr()) {
+        use recursion::{algebra, EGraph, Id, Saturator, StopReason, Term};
+
+        let constant = || algebra::<ExprFrame<PartiallyApplied>, Option<i64>>(|frame| match frame {
+            ExprFrame::Add(a, b) => Some(a?.wrapping_add(b?)),
+            ExprFrame::Sub(a, b) => Some(a?.wrapping_sub(b?)),
+            ExprFrame::Mul(a, b) => Some(a?.wrapping_mul(b?)),
+            ExprFrame::LiteralInt(x) => Some(x),
+        });
+        let mut egraph = EGraph::new();
+        let root = egraph.add_term(expr.clone());
+        // adding the same term again finds the existing e-classes
+        prop_assert_eq!(root, egraph.add_term(expr.clone()));
+
+        let constants = egraph.analyze(constant(), |a, b| a.or(b));
+        prop_assert_eq!(Some(wrapping_eval(&expr)), constants[&root]);
+
+        let fold = |egraph: &EGraph<ExprFrame<PartiallyApplied>>, frame: &ExprFrame<Id>| {
+            let folded = match frame {
+                ExprFrame::Add(a, b) => literal_in(egraph, *a)?.wrapping_add(literal_in(egraph, *b)?),
+                ExprFrame::Sub(a, b) => literal_in(egraph, *a)?.wrapping_sub(literal_in(egraph, *b)?),
+                ExprFrame::Mul(a, b) => literal_in(egraph, *a)?.wrapping_mul(literal_in(egraph, *b)?),
+                ExprFrame::LiteralInt(_) => return None,
+            };
+            Some(Term::node(ExprFrame::LiteralInt(folded)))
+        };
+        let saturation = Saturator::new().rule("fold", fold).run(&mut egraph);
+        prop_assert_eq!(StopReason::Saturated, saturation.stop_reason);
+
+        // the root's e-class now holds its value as a literal, which is the smallest term
+        let constants = egraph.analyze(constant(), |a, b| a.or(b));
+        prop_assert_eq!(Some(wrapping_eval(&expr)), constants[&egraph.find(root)]);
+        let (cost, best): (usize, Expr) = egraph.extract(root, size());
+        prop_assert_eq!(1, cost);
+        prop_assert_eq!(Expr::LiteralInt(wrapping_eval(&expr)), best);
+
+        // without any rules, extraction produces the original term
+        let mut egraph = EGraph::new();
+        let root = egraph.add_term(expr.clone());
+        let (_, extracted): (usize, Expr) = egraph.extract(root, size());
+        prop_assert_eq!(expr, extracted);
+    }
+}
@@ -0,0 +1,248 @@
+use proptest::prelude::*;
+use recursion::{MutualCollapsible, MutualExpandable, MutualFrame};
+
+/// expressions and statements of a small language, as an example of mutually recursive types
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    LiteralInt(i64),
+    Add(Box<Expr>, Box<Expr>),
+    Block(Vec<Stmt>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Print(Expr),
+    Assert(Expr, Expr),
+}
+
+pub enum ExprFrame<E, S> {
+    LiteralInt(i64),
+    Add(E, E),
+    Block(Vec<S>, E),
+}
+
+pub enum StmtFrame<E> {
+    Print(E),
+    Assert(E, E),
+}
+
+pub enum Lang {}
+
+impl MutualFrame for Lang {
+    type First<E, S> = ExprFrame<E, S>;
+    type Second<E, S> = StmtFrame<E>;
+
+    fn map_first<A, B, C, D>(
+        input: ExprFrame<A, B>,
+        mut f: impl FnMut(A) -> C,
+        g: impl FnMut(B) -> D,
+    ) -> ExprFrame<C, D> {
+        match input {
+            ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+            ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+            ExprFrame::Block(stmts, e) => {
+                let stmts = stmts.into_iter().map(g).collect();
+                ExprFrame::Block(stmts, f(e))
+            }
+        }
+    }
+
+    fn map_second<A, B, C, D>(
+        input: StmtFrame<A>,
+        mut f: impl FnMut(A) -> C,
+        _g: impl FnMut(B) -> D,
+    ) -> StmtFrame<C> {
+        match input {
+            StmtFrame::Print(e) => StmtFrame::Print(f(e)),
+            StmtFrame::Assert(a, b) => StmtFrame::Assert(f(a), f(b)),
+        }
+    }
+}
+
+impl<'a> MutualCollapsible for &'a Expr {
+    type FrameToken = Lang;
+    type Second = &'a Stmt;
+
+    fn into_first_frame(self) -> ExprFrame<&'a Expr, &'a Stmt> {
+        match self {
+            Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+            Expr::Add(a, b) => ExprFrame::Add(a, b),
+            Expr::Block(stmts, e) => ExprFrame::Block(stmts.iter().collect(), e),
+        }
+    }
+
+    fn into_second_frame(stmt: &'a Stmt) -> StmtFrame<&'a Expr> {
+        match stmt {
+            Stmt::Print(e) => StmtFrame::Print(e),
+            Stmt::Assert(a, b) => StmtFrame::Assert(a, b),
+        }
+    }
+}
+
+impl MutualExpandable for Expr {
+    type FrameToken = Lang;
+    type Second = Stmt;
+
+    fn from_first_frame(frame: ExprFrame<Expr, Stmt>) -> Expr {
+        match frame {
+            ExprFrame::LiteralInt(x) => Expr::LiteralInt(x),
+            ExprFrame::Add(a, b) => Expr::Add(Box::new(a), Box::new(b)),
+            ExprFrame::Block(stmts, e) => Expr::Block(stmts, Box::new(e)),
+        }
+    }
+
+    fn from_second_frame(frame: StmtFrame<Expr>) -> Stmt {
+        match frame {
+            StmtFrame::Print(e) => Stmt::Print(e),
+            StmtFrame::Assert(a, b) => Stmt::Assert(a, b),
+        }
+    }
+}
+
+// derived drop glue would recurse once per level of nesting
+impl Drop for Expr {
+    fn drop(&mut self) {
+        fn take(expr: &mut Expr) -> Expr {
+            std::mem::replace(expr, Expr::LiteralInt(0))
+        }
+
+        fn take_children(expr: &mut Expr, stack: &mut Vec<Expr>) {
+            match expr {
+                Expr::LiteralInt(_) => {}
+                Expr::Add(a, b) => stack.extend([take(a), take(b)]),
+                Expr::Block(stmts, e) => {
+                    for stmt in std::mem::take(stmts) {
+                        match stmt {
+                            Stmt::Print(e) => stack.push(e),
+                            Stmt::Assert(a, b) => stack.extend([a, b]),
+                        }
+                    }
+                    stack.push(take(e));
+                }
+            }
+        }
+
+        let mut stack = Vec::new();
+        take_children(self, &mut stack);
+        while let Some(mut expr) = stack.pop() {
+            take_children(&mut expr, &mut stack);
+        }
+    }
+}
+
+pub fn arb_expr() -> impl Strategy<Value = Expr> {
+    let leaf = any::<i8>().prop_map(|x| Expr::LiteralInt(x as i64));
+    leaf.prop_recursive(8, 256, 4, |inner| {
+        let stmt = prop_oneof![
+            inner.clone().prop_map(Stmt::Print),
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| Stmt::Assert(a, b)),
+        ];
+        prop_oneof![
+            (inner.clone(), inner.clone()).prop_map(|(a, b)| Expr::Add(Box::new(a), Box::new(b))),
+            (prop::collection::vec(stmt, 0..4), inner)
+                .prop_map(|(stmts, e)| Expr::Block(stmts, Box::new(e))),
+        ]
+    })
+}
+
+#[cfg(test)]
+fn render_expr(frame: ExprFrame<String, String>) -> String {
+    match frame {
+        ExprFrame::LiteralInt(x) => x.to_string(),
+        ExprFrame::Add(a, b) => format!("({} + {})", a, b),
+        ExprFrame::Block(stmts, e) => format!("{{ {}{} }}", stmts.concat(), e),
+    }
+}
+
+#[cfg(test)]
+fn render_stmt(frame: StmtFrame<String>) -> String {
+    match frame {
+        StmtFrame::Print(e) => format!("print {}; ", e),
+        StmtFrame::Assert(a, b) => format!("assert {} == {}; ", a, b),
+    }
+}
+
+#[test]
+fn lang_deep() {
+    use recursion::{MutualCollapsibleExt, MutualExpandableExt, Sort};
+
+    // blocks nested 100k deep, each printing the next
+    let nested = Expr::expand_mutual(
+        Sort::First(100_000),
+        |depth: u32| match depth {
+            0 => ExprFrame::LiteralInt(0),
+            _ => ExprFrame::Block(vec![depth], 0),
+        },
+        |depth: u32| StmtFrame::Print(depth - 1),
+    )
+    .first()
+    .unwrap();
+
+    let depth = <&Expr>::collapse_mutual(
+        Sort::First(&nested),
+        |frame: ExprFrame<usize, usize>| match frame {
+            ExprFrame::LiteralInt(_) => 0,
+            ExprFrame::Add(a, b) => a.max(b),
+            ExprFrame::Block(stmts, e) => stmts.into_iter().fold(e, usize::max) + 1,
+        },
+        |frame: StmtFrame<usize>| match frame {
+            StmtFrame::Print(e) => e,
+            StmtFrame::Assert(a, b) => a.max(b),
+        },
+    );
+    assert_eq!(Sort::First(100_000), depth);
+}
+
+#[cfg(test)]
+proptest! {
+    #[test]
+    fn lang_collapse_mutual(expr in arb_expr()) {
+        use recursion::{MutualCollapsibleExt, Sort};
+
+        fn naive_render_expr(expr: &Expr) -> String {
+            match expr {
+                Expr::LiteralInt(x) => x.to_string(),
+                Expr::Add(a, b) => format!("({} + {})", naive_render_expr(a), naive_render_expr(b)),
+                Expr::Block(stmts, e) => {
+                    let stmts: String = stmts.iter().map(naive_render_stmt).collect();
+                    format!("{{ {}{} }}", stmts, naive_render_expr(e))
+                }
+            }
+        }
+
+        fn naive_render_stmt(stmt: &Stmt) -> String {
+            match stmt {
+                Stmt::Print(e) => format!("print {}; ", naive_render_expr(e)),
+                Stmt::Assert(a, b) => format!("assert {} == {}; ", naive_render_expr(a), naive_render_expr(b)),
+            }
+        }
+
+        let rendered = <&Expr>::collapse_mutual(Sort::First(&expr), render_expr, render_stmt);
+        prop_assert_eq!(Sort::First(naive_render_expr(&expr)), rendered);
+
+        let stmt = Stmt::Print(expr);
+        let rendered = <&Expr>::collapse_mutual(Sort::Second(&stmt), render_expr, render_stmt);
+        prop_assert_eq!(Sort::Second(naive_render_stmt(&stmt)), rendered);
+    }
+
+    #[test]
+    fn lang_expand_mutual(expr in arb_expr()) {
+        use recursion::{MutualExpandableExt, Sort};
+
+        // expanding from references to an existing structure rebuilds it
+        let expanded = Expr::expand_mutual(
+            Sort::First(&expr),
+            MutualCollapsible::into_first_frame,
+            <&Expr>::into_second_frame,
+        );
+        prop_assert_eq!(Sort::First(expr.clone()), expanded);
+
+        let stmt = Stmt::Assert(expr.clone(), expr);
+        let expanded = Expr::expand_mutual(
+            Sort::Second(&stmt),
+            MutualCollapsible::into_first_frame,
+            <&Expr>::into_second_frame,
+        );
+        prop_assert_eq!(Sort::Second(stmt), expanded);
+    }
+}
@@ -1 +1,3 @@
 pub mod expr;
+pub mod lang;
+pub mod tree;
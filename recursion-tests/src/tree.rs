@@ -0,0 +1,106 @@
+use proptest::prelude::*;
+use recursion::{BimappableFrame, Collapsible, Expandable, MappableFrame, PartiallyApplied};
+
+/// simple binary tree with values at its leaves
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tree<V> {
+    Leaf(V),
+    Node(Box<Tree<V>>, Box<Tree<V>>),
+}
+
+pub enum TreeFrame<V, A> {
+    Leaf(V),
+    Node(A, A),
+}
+
+impl<V> MappableFrame for TreeFrame<V, PartiallyApplied> {
+    type Frame<X> = TreeFrame<V, X>;
+
+    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        match input {
+            TreeFrame::Leaf(v) => TreeFrame::Leaf(v),
+            TreeFrame::Node(a, b) => TreeFrame::Node(f(a), f(b)),
+        }
+    }
+}
+
+impl<V> BimappableFrame for TreeFrame<V, PartiallyApplied> {
+    type Payload = V;
+    type WithPayload<P> = TreeFrame<P, PartiallyApplied>;
+
+    fn bimap_frame<A, B, P>(
+        input: Self::Frame<A>,
+        mut payload: impl FnMut(V) -> P,
+        mut f: impl FnMut(A) -> B,
+    ) -> TreeFrame<P, B> {
+        match input {
+            TreeFrame::Leaf(v) => TreeFrame::Leaf(payload(v)),
+            TreeFrame::Node(a, b) => TreeFrame::Node(f(a), f(b)),
+        }
+    }
+}
+
+impl<'a, V> Collapsible for &'a Tree<V> {
+    type FrameToken = TreeFrame<&'a V, PartiallyApplied>;
+
+    fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+        match self {
+            Tree::Leaf(v) => TreeFrame::Leaf(v),
+            Tree::Node(a, b) => TreeFrame::Node(a, b),
+        }
+    }
+}
+
+impl<V> Expandable for Tree<V> {
+    type FrameToken = TreeFrame<V, PartiallyApplied>;
+
+    fn from_frame(val: <Self::FrameToken as MappableFrame>::Frame<Self>) -> Self {
+        match val {
+            TreeFrame::Leaf(v) => Tree::Leaf(v),
+            TreeFrame::Node(a, b) => Tree::Node(Box::new(a), Box::new(b)),
+        }
+    }
+}
+
+pub fn arb_tree() -> impl Strategy<Value = Tree<i64>> {
+    let leaf = any::<i64>().prop_map(Tree::Leaf);
+    leaf.prop_recursive(8, 256, 2, |inner| {
+        (inner.clone(), inner).prop_map(|(a, b)| Tree::Node(Box::new(a), Box::new(b)))
+    })
+}
+
+#[cfg(test)]
+proptest! {
+    #[test]
+    fn tree_map_payload(tree in arb_tree()) {
+        use recursion::CollapsiblePayloadExt;
+
+        fn naive_map(tree: &Tree<i64>) -> Tree<f64> {
+            match tree {
+                Tree::Leaf(x) => Tree::Leaf(*x as f64 / 2.0),
+                Tree::Node(a, b) => Tree::Node(Box::new(naive_map(a)), Box::new(naive_map(b))),
+            }
+        }
+
+        let mapped: Tree<f64> = (&tree).map_payload(|x: &i64| *x as f64 / 2.0);
+        prop_assert_eq!(naive_map(&tree), mapped);
+
+        // the payload is visited once per leaf
+        let mut visited = Vec::new();
+        let _: Tree<()> = (&tree).map_payload(|x: &i64| visited.push(*x));
+        fn leaves(tree: &Tree<i64>, out: &mut Vec<i64>) {
+            match tree {
+                Tree::Leaf(x) => out.push(*x),
+                Tree::Node(a, b) => {
+                    leaves(a, out);
+                    leaves(b, out);
+                }
+            }
+        }
+        let mut expected = Vec::new();
+        leaves(&tree, &mut expected);
+        visited.sort_unstable();
+        expected.sort_unstable();
+        prop_assert_eq!(expected, visited);
+    }
+}
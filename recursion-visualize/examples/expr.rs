@@ -10,14 +10,24 @@ mod expr1 {
         Mul(Box<Expr>, Box<Expr>),
         LiteralInt(i64),
     }
+    // left associative infix operators, parenthesizing operands with lower precedence
+    fn infix(a: Child, op: &'static str, b: Child, precedence: u32) -> Layout {
+        Layout::new()
+            .precedence(precedence)
+            .child_at(a, precedence)
+            .text(op)
+            .child_at(b, precedence + 1)
+    }
+
     impl Display for Expr {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            match self {
-                Expr::Add(a, b) => write!(f, "{} + {}", a, b),
-                Expr::Sub(a, b) => write!(f, "{} - {}", a, b),
-                Expr::Mul(a, b) => write!(f, "{} * {}", a, b),
-                Expr::LiteralInt(x) => write!(f, "{}", x),
-            }
+            let layout_frame = |frame| match frame {
+                ExprFrame::Add(a, b) => infix(a, " + ", b, 1),
+                ExprFrame::Sub(a, b) => infix(a, " - ", b, 1),
+                ExprFrame::Mul(a, b) => infix(a, " * ", b, 2),
+                ExprFrame::LiteralInt(x) => Layout::new().text(x.to_string()),
+            };
+            TreePrinter::new().write(self, layout_frame, f)
         }
     }
 
@@ -62,7 +72,7 @@ mod expr1 {
             }
         }
     }
-    impl<'a> Collapsible for &'a Expr {
+    impl Collapsible for &Expr {
         type FrameToken = ExprFrame<PartiallyApplied>;
         fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
             match self {
@@ -117,17 +127,28 @@ mod expr2 {
         Div(Box<Expr>, Box<Expr>),
         LiteralInt(i64),
     }
+    // left associative infix operators, parenthesizing operands with lower precedence
+    fn infix(a: Child, op: &'static str, b: Child, precedence: u32) -> Layout {
+        Layout::new()
+            .precedence(precedence)
+            .child_at(a, precedence)
+            .text(op)
+            .child_at(b, precedence + 1)
+    }
+
     impl Display for Expr {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            match self {
-                Expr::Add(a, b) => write!(f, "{} + {}", a, b),
-                Expr::Sub(a, b) => write!(f, "{} - {}", a, b),
-                Expr::Mul(a, b) => write!(f, "{} * {}", a, b),
-                Expr::Div(a, b) => write!(f, "{} / {}", a, b),
-                Expr::LiteralInt(x) => write!(f, "{}", x),
-            }
+            let layout_frame = |frame| match frame {
+                ExprFrame::Add(a, b) => infix(a, " + ", b, 1),
+                ExprFrame::Sub(a, b) => infix(a, " - ", b, 1),
+                ExprFrame::Mul(a, b) => infix(a, " * ", b, 2),
+                ExprFrame::Div(a, b) => infix(a, " / ", b, 2),
+                ExprFrame::LiteralInt(x) => Layout::new().text(x.to_string()),
+            };
+            TreePrinter::new().write(self, layout_frame, f)
         }
     }
+    #[allow(dead_code)]
     pub fn add(a: Expr, b: Expr) -> Expr {
         Expr::Add(Box::new(a), Box::new(b))
     }
@@ -173,7 +194,7 @@ mod expr2 {
             }
         }
     }
-    impl<'a> Collapsible for &'a Expr {
+    impl Collapsible for &Expr {
         type FrameToken = ExprFrame<PartiallyApplied>;
         fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
             match self {
@@ -210,6 +231,7 @@ fn main() {
 
         let (evaluated, viz) = eval(&expr);
         assert_eq!(evaluated, -3);
+        assert_eq!(expr.to_string(), "(1 - 2) * 3");
 
         viz.label("Evaluate Expr".to_string(), "(1 - 2) * 3".to_string())
             .write("eval.html".to_string());
@@ -232,6 +254,7 @@ fn main() {
         let (valid_res, valid_viz) = try_eval(&valid_expr);
         let (invalid_res, invalid_viz) = try_eval(&invalid_expr);
 
+        assert_eq!(valid_expr.to_string(), "(1 - 7) / 3");
         assert_eq!(valid_res, Ok(-2));
         assert_eq!(invalid_res, Err("cannot divide by zero"));
 
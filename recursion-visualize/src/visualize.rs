@@ -1,6 +1,9 @@
 use std::fmt::Display;
 
-use recursion::{Collapsible, Expandable, MappableFrame};
+use recursion::{
+    Algebra, Coalgebra, Collapsible, Expandable, IntoAlgebra, IntoCoalgebra, IntoTryAlgebra,
+    MappableFrame, TryAlgebra,
+};
 
 /// The ability to collapse a value into some output type, frame by frame
 pub trait CollapsibleVizExt: Collapsible
@@ -8,19 +11,27 @@ where
     Self: Sized + Display,
     <Self::FrameToken as MappableFrame>::Frame<()>: Display,
 {
-    fn collapse_frames_v<Out>(
+    fn collapse_frames_v<M, A: IntoAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
-    ) -> (Out, Viz)
+        collapse_frame: A,
+    ) -> (<A::Algebra as Algebra<Self::FrameToken>>::Out, Viz)
     where
-        Out: Display;
+        <A::Algebra as Algebra<Self::FrameToken>>::Carrier: Display;
 
-    fn try_collapse_frames_v<Out, E: Display>(
+    #[allow(clippy::type_complexity)]
+    fn try_collapse_frames_v<M, A: IntoTryAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Result<Out, E>,
-    ) -> (Result<Out, E>, Viz)
+        collapse_frame: A,
+    ) -> (
+        Result<
+            <A::Algebra as TryAlgebra<Self::FrameToken>>::Out,
+            <A::Algebra as TryAlgebra<Self::FrameToken>>::Error,
+        >,
+        Viz,
+    )
     where
-        Out: Display;
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Carrier: Display,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Error: Display;
 }
 
 impl<X: Collapsible> CollapsibleVizExt for X
@@ -28,28 +39,43 @@ where
     Self: Sized + Display,
     <Self::FrameToken as MappableFrame>::Frame<()>: Display,
 {
-    fn collapse_frames_v<Out>(
+    fn collapse_frames_v<M, A: IntoAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
-    ) -> (Out, Viz)
+        collapse_frame: A,
+    ) -> (<A::Algebra as Algebra<Self::FrameToken>>::Out, Viz)
     where
-        Out: Display,
+        <A::Algebra as Algebra<Self::FrameToken>>::Carrier: Display,
     {
-        expand_and_collapse_v::<Self::FrameToken, Self, Out>(self, Self::into_frame, collapse_frame)
+        let mut algebra = collapse_frame.into_algebra();
+        let (carrier, viz) =
+            expand_and_collapse_v::<Self::FrameToken, Self, _>(self, Self::into_frame, |frame| {
+                algebra.collapse_frame(frame)
+            });
+        (algebra.finish(carrier), viz)
     }
 
-    fn try_collapse_frames_v<Out, E: Display>(
+    #[allow(clippy::type_complexity)]
+    fn try_collapse_frames_v<M, A: IntoTryAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Result<Out, E>,
-    ) -> (Result<Out, E>, Viz)
+        collapse_frame: A,
+    ) -> (
+        Result<
+            <A::Algebra as TryAlgebra<Self::FrameToken>>::Out,
+            <A::Algebra as TryAlgebra<Self::FrameToken>>::Error,
+        >,
+        Viz,
+    )
     where
-        Out: Display,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Carrier: Display,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Error: Display,
     {
-        try_expand_and_collapse_v::<Self::FrameToken, Self, Out, E>(
+        let mut algebra = collapse_frame.into_try_algebra();
+        let (carrier, viz) = try_expand_and_collapse_v::<Self::FrameToken, Self, _, _>(
             self,
             |x| Ok(Self::into_frame(x)),
-            collapse_frame,
-        )
+            |frame| algebra.try_collapse_frame(frame),
+        );
+        (carrier.map(|carrier| algebra.finish(carrier)), viz)
     }
 }
 
@@ -58,12 +84,12 @@ where
     Self: Sized + Display,
     <Self::FrameToken as MappableFrame>::Frame<()>: Display,
 {
-    fn expand_frames_v<In>(
+    fn expand_frames_v<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
         input: In,
-        expand_frame: impl FnMut(In) -> <Self::FrameToken as MappableFrame>::Frame<In>,
+        expand_frame: C,
     ) -> (Self, Viz)
     where
-        In: Display;
+        <C::Coalgebra as Coalgebra<Self::FrameToken>>::Seed: Display;
 }
 
 impl<X: Expandable> ExpandableVizExt for X
@@ -71,14 +97,20 @@ where
     Self: Sized + Display,
     <Self::FrameToken as MappableFrame>::Frame<()>: Display,
 {
-    fn expand_frames_v<In>(
+    fn expand_frames_v<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
         input: In,
-        expand_frame: impl FnMut(In) -> <Self::FrameToken as MappableFrame>::Frame<In>,
+        expand_frame: C,
     ) -> (Self, Viz)
     where
-        In: Display,
+        <C::Coalgebra as Coalgebra<Self::FrameToken>>::Seed: Display,
     {
-        expand_and_collapse_v::<Self::FrameToken, In, Self>(input, expand_frame, Self::from_frame)
+        let mut coalgebra = expand_frame.into_coalgebra();
+        let seed = coalgebra.start(input);
+        expand_and_collapse_v::<Self::FrameToken, _, Self>(
+            seed,
+            |seed| coalgebra.expand_frame(seed),
+            Self::from_frame,
+        )
     }
 }
 
@@ -133,7 +165,7 @@ impl Viz {
             info_header,
             info_txt,
         }];
-        actions.extend(self.actions.into_iter());
+        actions.extend(self.actions);
         self.actions = actions;
 
         self
@@ -145,7 +177,7 @@ impl Viz {
             info_txt,
             info_header,
         });
-        actions.extend(next.actions.into_iter());
+        actions.extend(next.actions);
 
         Self {
             seed_txt: self.seed_txt,
@@ -305,7 +337,7 @@ where
                 });
 
                 todo.push(State::PostVisit((viz_node_id, node)));
-                todo.extend(topush.into_iter());
+                todo.extend(topush);
             }
             State::PostVisit((viz_node_id, node)) => {
                 let node = F::map_frame(node, |_: ()| vals.pop().unwrap());
@@ -403,7 +435,7 @@ where
                 });
 
                 todo.push(State::PostVisit((viz_node_id, node)));
-                todo.extend(topush.into_iter());
+                todo.extend(topush);
             }
             State::PostVisit((viz_node_id, node)) => {
                 let node = F::map_frame(node, |_: ()| vals.pop().unwrap());
@@ -438,7 +470,7 @@ where
 }
 
 //TODO/FIXME: something better than this. that said, this is in experimental so :shrug_emoji:
-static TEMPLATE_BEFORE: &'static str = r###"
+static TEMPLATE_BEFORE: &str = r###"
 <!DOCTYPE html>
 <meta charset="UTF-8">
 <style>
@@ -509,7 +541,7 @@ body {
 
 const data = "###;
 
-static TEMPLATE_AFTER: &'static str = r###"
+static TEMPLATE_AFTER: &str = r###"
 
  var treeData = data.root;
 
@@ -0,0 +1,597 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::frame::MappableFrame;
+
+/// A named, reusable function for collapsing frames, as accepted by [`crate::CollapsibleExt::collapse_frames`]
+/// and the other collapsing traversals alongside plain closures (see [`IntoAlgebra`]).
+///
+/// Each node is collapsed into a `Carrier`, and the root's carrier is then turned into the final `Out`
+/// via [`Algebra::finish`]. Keeping these separate allows an algebra's output to be mapped over via
+/// [`Algebra::map`] without affecting how children are combined.
+///
+/// Algebras can be built from closures via [`algebra`], and combined via [`Algebra::zip`] such that both run
+/// in a single traversal.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # #[derive(Clone)]
+/// # enum ExprFrame<A> {
+/// #     Add(A, A),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// # enum Expr {
+/// #     Add(Box<Expr>, Box<Expr>),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl<'a> Collapsible for &'a Expr {
+/// #     type FrameToken = ExprFrame<PartiallyApplied>;
+/// #     fn into_frame(self) -> ExprFrame<&'a Expr> {
+/// #         match self {
+/// #             Expr::Add(a, b) => ExprFrame::Add(a, b),
+/// #             Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+/// #         }
+/// #     }
+/// # }
+/// type Token = ExprFrame<PartiallyApplied>;
+///
+/// let eval = algebra::<Token, i64>(|frame| match frame {
+///     ExprFrame::Add(a, b) => a + b,
+///     ExprFrame::LiteralInt(x) => x,
+/// });
+///
+/// let depth = algebra::<Token, usize>(|frame| match frame {
+///     ExprFrame::Add(a, b) => 1 + a.max(b),
+///     ExprFrame::LiteralInt(_) => 1,
+/// });
+///
+/// // 1 + (2 + 3)
+/// let lit = |x| Box::new(Expr::LiteralInt(x));
+/// let expr = Expr::Add(lit(1), Box::new(Expr::Add(lit(2), lit(3))));
+///
+/// let both = eval.zip(depth).map(|(value, depth)| format!("{} at depth {}", value, depth));
+/// assert_eq!((&expr).collapse_frames(both), "6 at depth 3");
+/// ```
+pub trait Algebra<F: MappableFrame> {
+    /// The value each node is collapsed into
+    type Carrier;
+
+    /// The final result, produced from the root's carrier
+    type Out;
+
+    /// Collapse a single frame, with each child position already collapsed
+    fn collapse_frame(&mut self, frame: F::Frame<Self::Carrier>) -> Self::Carrier;
+
+    /// Produce the final result from the root's carrier
+    fn finish(&mut self, carrier: Self::Carrier) -> Self::Out;
+
+    /// Run this algebra and `other` together in a single traversal, producing both of their results.
+    /// Requires frames with their child positions erased to be `Clone`, as each frame is split in two.
+    fn zip<B: Algebra<F>>(self, other: B) -> Zip<F, Self, B>
+    where
+        Self: Sized,
+    {
+        Zip {
+            left: self,
+            right: other,
+            buffers: Unzip::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Apply some function `f` to this algebra's final result
+    fn map<P, G: FnMut(Self::Out) -> P>(self, f: G) -> Map<Self, G>
+    where
+        Self: Sized,
+    {
+        Map(self, f)
+    }
+
+    /// Lift this algebra into a [`TryAlgebra`] that never fails, such that it can be combined with
+    /// fallible algebras with the error type `E`
+    fn fallible<E>(self) -> Fallible<Self, E>
+    where
+        Self: Sized,
+    {
+        Fallible(self, PhantomData)
+    }
+}
+
+/// A named, reusable fallible function for collapsing frames, as accepted by
+/// [`crate::CollapsibleExt::try_collapse_frames`] alongside plain closures. See [`Algebra`].
+pub trait TryAlgebra<F: MappableFrame> {
+    /// The value each node is collapsed into
+    type Carrier;
+
+    /// The final result, produced from the root's carrier
+    type Out;
+
+    /// The error produced when collapsing a frame fails
+    type Error;
+
+    /// Collapse a single frame, with each child position already collapsed
+    fn try_collapse_frame(
+        &mut self,
+        frame: F::Frame<Self::Carrier>,
+    ) -> Result<Self::Carrier, Self::Error>;
+
+    /// Produce the final result from the root's carrier
+    fn finish(&mut self, carrier: Self::Carrier) -> Self::Out;
+
+    /// Run this algebra and `other` together in a single traversal, stopping at the first error from either
+    fn zip<B: TryAlgebra<F, Error = Self::Error>>(self, other: B) -> TryZip<F, Self, B>
+    where
+        Self: Sized,
+    {
+        TryZip {
+            left: self,
+            right: other,
+            buffers: Unzip::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Apply some function `f` to this algebra's final result
+    fn map<P, G: FnMut(Self::Out) -> P>(self, f: G) -> Map<Self, G>
+    where
+        Self: Sized,
+    {
+        Map(self, f)
+    }
+}
+
+/// A named, reusable function for expanding seeds into frames, as accepted by
+/// [`crate::ExpandableExt::expand_frames`] and the other expanding traversals alongside plain closures
+/// (see [`IntoCoalgebra`]).
+///
+/// Each input is turned into a `Seed` via [`Coalgebra::start`], which is then expanded frame by frame.
+/// Keeping these separate allows the input to be adapted via [`Coalgebra::contramap`] without affecting
+/// how seeds are expanded.
+///
+/// ```rust
+/// # use recursion::*;
+/// #[derive(Debug, PartialEq)]
+/// struct Nat(Option<Box<Nat>>);
+///
+/// impl Expandable for Nat {
+///     type FrameToken = Option<PartiallyApplied>;
+///
+///     fn from_frame(frame: Option<Nat>) -> Nat {
+///         Nat(frame.map(Box::new))
+///     }
+/// }
+///
+/// let count_down = coalgebra::<Option<PartiallyApplied>, _>(|n: usize| n.checked_sub(1));
+/// let from_str = count_down.contramap(|s: &str| s.len());
+///
+/// let two = Nat(Some(Box::new(Nat(Some(Box::new(Nat(None)))))));
+/// assert_eq!(Nat::expand_frames("ab", from_str), two);
+/// ```
+pub trait Coalgebra<F: MappableFrame> {
+    /// The input from which expansion starts
+    type In;
+
+    /// The value each node is expanded from
+    type Seed;
+
+    /// Produce the root's seed from the input
+    fn start(&mut self, input: Self::In) -> Self::Seed;
+
+    /// Expand a single seed into a frame, with a seed in each child position
+    fn expand_frame(&mut self, seed: Self::Seed) -> F::Frame<Self::Seed>;
+
+    /// Apply some function `f` to the input before expanding it
+    fn contramap<I, G: FnMut(I) -> Self::In>(self, f: G) -> Contramap<Self, G, I>
+    where
+        Self: Sized,
+    {
+        Contramap(self, f, PhantomData)
+    }
+}
+
+/// Conversion into an [`Algebra`], implemented by every algebra and by any closure from `Frame<Out>` to `Out`.
+/// This allows traversals such as [`crate::CollapsibleExt::collapse_frames`] to accept either.
+///
+/// The `Marker` parameter only keeps the two implementations apart, and is always inferred.
+pub trait IntoAlgebra<F: MappableFrame, Marker> {
+    /// The algebra this converts into
+    type Algebra: Algebra<F>;
+
+    /// Convert this into an algebra
+    fn into_algebra(self) -> Self::Algebra;
+}
+
+impl<F: MappableFrame, A: Algebra<F>> IntoAlgebra<F, ()> for A {
+    type Algebra = A;
+
+    fn into_algebra(self) -> A {
+        self
+    }
+}
+
+impl<F, Out, Func> IntoAlgebra<F, fn(Out)> for Func
+where
+    F: MappableFrame,
+    Func: FnMut(F::Frame<Out>) -> Out,
+{
+    type Algebra = FnAlgebra<F, Out, Func>;
+
+    fn into_algebra(self) -> Self::Algebra {
+        FnAlgebra(self, PhantomData)
+    }
+}
+
+/// Conversion into a [`TryAlgebra`], implemented by every fallible algebra and by any closure from
+/// `Frame<Out>` to `Result<Out, E>`. See [`IntoAlgebra`].
+pub trait IntoTryAlgebra<F: MappableFrame, Marker> {
+    /// The fallible algebra this converts into
+    type Algebra: TryAlgebra<F>;
+
+    /// Convert this into a fallible algebra
+    fn into_try_algebra(self) -> Self::Algebra;
+}
+
+impl<F: MappableFrame, A: TryAlgebra<F>> IntoTryAlgebra<F, ()> for A {
+    type Algebra = A;
+
+    fn into_try_algebra(self) -> A {
+        self
+    }
+}
+
+impl<F, Out, E, Func> IntoTryAlgebra<F, fn(Out) -> E> for Func
+where
+    F: MappableFrame,
+    Func: FnMut(F::Frame<Out>) -> Result<Out, E>,
+{
+    type Algebra = FnAlgebra<F, Out, Func>;
+
+    fn into_try_algebra(self) -> Self::Algebra {
+        FnAlgebra(self, PhantomData)
+    }
+}
+
+/// Conversion into a [`Coalgebra`] with the input `In`, implemented by every coalgebra and by any closure from
+/// `Seed` to `Frame<Seed>`. This allows traversals such as [`crate::ExpandableExt::expand_frames`] to accept
+/// either. The input is a parameter rather than read off the coalgebra so that it's known before the closure's
+/// argument is inferred.
+///
+/// The `Marker` parameter only keeps the two implementations apart, and is always inferred.
+pub trait IntoCoalgebra<F: MappableFrame, In, Marker> {
+    /// The coalgebra this converts into
+    type Coalgebra: Coalgebra<F, In = In>;
+
+    /// Convert this into a coalgebra
+    fn into_coalgebra(self) -> Self::Coalgebra;
+}
+
+impl<F: MappableFrame, C: Coalgebra<F>> IntoCoalgebra<F, C::In, ()> for C {
+    type Coalgebra = C;
+
+    fn into_coalgebra(self) -> C {
+        self
+    }
+}
+
+impl<F, Seed, Func> IntoCoalgebra<F, Seed, fn(Seed)> for Func
+where
+    F: MappableFrame,
+    Func: FnMut(Seed) -> F::Frame<Seed>,
+{
+    type Coalgebra = FnCoalgebra<F, Seed, Func>;
+
+    fn into_coalgebra(self) -> Self::Coalgebra {
+        FnCoalgebra(self, PhantomData)
+    }
+}
+
+/// Build an [`Algebra`] from a closure, for frames of type `F`
+pub fn algebra<F: MappableFrame, Out>(
+    collapse_frame: impl FnMut(F::Frame<Out>) -> Out,
+) -> FnAlgebra<F, Out, impl FnMut(F::Frame<Out>) -> Out> {
+    FnAlgebra(collapse_frame, PhantomData)
+}
+
+/// Build a [`TryAlgebra`] from a fallible closure, for frames of type `F`
+#[allow(clippy::type_complexity)]
+pub fn try_algebra<F: MappableFrame, Out, E>(
+    collapse_frame: impl FnMut(F::Frame<Out>) -> Result<Out, E>,
+) -> FnAlgebra<F, Out, impl FnMut(F::Frame<Out>) -> Result<Out, E>> {
+    FnAlgebra(collapse_frame, PhantomData)
+}
+
+/// Build a [`Coalgebra`] from a closure, for frames of type `F`
+pub fn coalgebra<F: MappableFrame, Seed>(
+    expand_frame: impl FnMut(Seed) -> F::Frame<Seed>,
+) -> FnCoalgebra<F, Seed, impl FnMut(Seed) -> F::Frame<Seed>> {
+    FnCoalgebra(expand_frame, PhantomData)
+}
+
+/// An algebra built from a closure, see [`algebra`] and [`try_algebra`]
+pub struct FnAlgebra<F, Out, Func>(Func, PhantomData<fn(F) -> Out>);
+
+impl<F, Out, Func> Algebra<F> for FnAlgebra<F, Out, Func>
+where
+    F: MappableFrame,
+    Func: FnMut(F::Frame<Out>) -> Out,
+{
+    type Carrier = Out;
+    type Out = Out;
+
+    fn collapse_frame(&mut self, frame: F::Frame<Out>) -> Out {
+        (self.0)(frame)
+    }
+
+    fn finish(&mut self, carrier: Out) -> Out {
+        carrier
+    }
+}
+
+impl<F, Out, E, Func> TryAlgebra<F> for FnAlgebra<F, Out, Func>
+where
+    F: MappableFrame,
+    Func: FnMut(F::Frame<Out>) -> Result<Out, E>,
+{
+    type Carrier = Out;
+    type Out = Out;
+    type Error = E;
+
+    fn try_collapse_frame(&mut self, frame: F::Frame<Out>) -> Result<Out, E> {
+        (self.0)(frame)
+    }
+
+    fn finish(&mut self, carrier: Out) -> Out {
+        carrier
+    }
+}
+
+/// A coalgebra built from a closure, see [`coalgebra`]
+pub struct FnCoalgebra<F, Seed, Func>(Func, PhantomData<fn(F) -> Seed>);
+
+impl<F, Seed, Func> Coalgebra<F> for FnCoalgebra<F, Seed, Func>
+where
+    F: MappableFrame,
+    Func: FnMut(Seed) -> F::Frame<Seed>,
+{
+    type In = Seed;
+    type Seed = Seed;
+
+    fn start(&mut self, input: Seed) -> Seed {
+        input
+    }
+
+    fn expand_frame(&mut self, seed: Seed) -> F::Frame<Seed> {
+        (self.0)(seed)
+    }
+}
+
+/// Two algebras run together in a single traversal, see [`Algebra::zip`]
+pub struct Zip<F: MappableFrame, A: Algebra<F>, B: Algebra<F>> {
+    left: A,
+    right: B,
+    buffers: Unzip<A::Carrier, B::Carrier>,
+    _marker: PhantomData<fn(F)>,
+}
+
+impl<F, A, B> Algebra<F> for Zip<F, A, B>
+where
+    F: MappableFrame,
+    F::Frame<()>: Clone,
+    A: Algebra<F>,
+    B: Algebra<F>,
+{
+    type Carrier = (A::Carrier, B::Carrier);
+    type Out = (A::Out, B::Out);
+
+    fn collapse_frame(&mut self, frame: F::Frame<Self::Carrier>) -> Self::Carrier {
+        let (a, b) = self.buffers.unzip_frame::<F>(frame);
+        (self.left.collapse_frame(a), self.right.collapse_frame(b))
+    }
+
+    fn finish(&mut self, (a, b): Self::Carrier) -> Self::Out {
+        (self.left.finish(a), self.right.finish(b))
+    }
+}
+
+/// Two fallible algebras run together in a single traversal, see [`TryAlgebra::zip`]
+pub struct TryZip<F: MappableFrame, A: TryAlgebra<F>, B: TryAlgebra<F>> {
+    left: A,
+    right: B,
+    buffers: Unzip<A::Carrier, B::Carrier>,
+    _marker: PhantomData<fn(F)>,
+}
+
+impl<F, A, B> TryAlgebra<F> for TryZip<F, A, B>
+where
+    F: MappableFrame,
+    F::Frame<()>: Clone,
+    A: TryAlgebra<F>,
+    B: TryAlgebra<F, Error = A::Error>,
+{
+    type Carrier = (A::Carrier, B::Carrier);
+    type Out = (A::Out, B::Out);
+    type Error = A::Error;
+
+    fn try_collapse_frame(
+        &mut self,
+        frame: F::Frame<Self::Carrier>,
+    ) -> Result<Self::Carrier, Self::Error> {
+        let (a, b) = self.buffers.unzip_frame::<F>(frame);
+        Ok((
+            self.left.try_collapse_frame(a)?,
+            self.right.try_collapse_frame(b)?,
+        ))
+    }
+
+    fn finish(&mut self, (a, b): Self::Carrier) -> Self::Out {
+        (self.left.finish(a), self.right.finish(b))
+    }
+}
+
+// buffers for splitting each frame in two, reused between frames such that zipping doesn't allocate per node
+struct Unzip<L, R> {
+    lefts: Vec<L>,
+    rights: Vec<R>,
+}
+
+impl<L, R> Default for Unzip<L, R> {
+    fn default() -> Self {
+        Self {
+            lefts: Vec::new(),
+            rights: Vec::new(),
+        }
+    }
+}
+
+impl<L, R> Unzip<L, R> {
+    /// Split a frame of pairs into a pair of frames, via the frame's shape with its child positions erased
+    fn unzip_frame<F>(&mut self, frame: F::Frame<(L, R)>) -> (F::Frame<L>, F::Frame<R>)
+    where
+        F: MappableFrame,
+        F::Frame<()>: Clone,
+    {
+        let (lefts, rights) = (&mut self.lefts, &mut self.rights);
+        let shape = F::map_frame(frame, |(l, r)| {
+            lefts.push(l);
+            rights.push(r);
+        });
+
+        let mut lefts = self.lefts.drain(..);
+        let left = F::map_frame(shape.clone(), |()| {
+            lefts
+                .next()
+                .expect("map_frame visited a different number of positions")
+        });
+        let mut rights = self.rights.drain(..);
+        let right = F::map_frame(shape, |()| {
+            rights
+                .next()
+                .expect("map_frame visited a different number of positions")
+        });
+        (left, right)
+    }
+}
+
+/// An algebra with a function applied to its final result, see [`Algebra::map`]
+pub struct Map<A, G>(A, G);
+
+impl<F, A, G, P> Algebra<F> for Map<A, G>
+where
+    F: MappableFrame,
+    A: Algebra<F>,
+    G: FnMut(A::Out) -> P,
+{
+    type Carrier = A::Carrier;
+    type Out = P;
+
+    fn collapse_frame(&mut self, frame: F::Frame<Self::Carrier>) -> Self::Carrier {
+        self.0.collapse_frame(frame)
+    }
+
+    fn finish(&mut self, carrier: Self::Carrier) -> P {
+        (self.1)(self.0.finish(carrier))
+    }
+}
+
+impl<F, A, G, P> TryAlgebra<F> for Map<A, G>
+where
+    F: MappableFrame,
+    A: TryAlgebra<F>,
+    G: FnMut(A::Out) -> P,
+{
+    type Carrier = A::Carrier;
+    type Out = P;
+    type Error = A::Error;
+
+    fn try_collapse_frame(
+        &mut self,
+        frame: F::Frame<Self::Carrier>,
+    ) -> Result<Self::Carrier, Self::Error> {
+        self.0.try_collapse_frame(frame)
+    }
+
+    fn finish(&mut self, carrier: Self::Carrier) -> P {
+        (self.1)(self.0.finish(carrier))
+    }
+}
+
+/// An algebra lifted into a [`TryAlgebra`] that never fails, see [`Algebra::fallible`]
+pub struct Fallible<A, E>(A, PhantomData<fn() -> E>);
+
+impl<F, A, E> TryAlgebra<F> for Fallible<A, E>
+where
+    F: MappableFrame,
+    A: Algebra<F>,
+{
+    type Carrier = A::Carrier;
+    type Out = A::Out;
+    type Error = E;
+
+    fn try_collapse_frame(&mut self, frame: F::Frame<Self::Carrier>) -> Result<Self::Carrier, E> {
+        Ok(self.0.collapse_frame(frame))
+    }
+
+    fn finish(&mut self, carrier: Self::Carrier) -> Self::Out {
+        self.0.finish(carrier)
+    }
+}
+
+/// A coalgebra with a function applied to its input, see [`Coalgebra::contramap`]
+pub struct Contramap<C, G, I>(C, G, PhantomData<fn(I)>);
+
+impl<F, C, G, I> Coalgebra<F> for Contramap<C, G, I>
+where
+    F: MappableFrame,
+    C: Coalgebra<F>,
+    G: FnMut(I) -> C::In,
+{
+    type In = I;
+    type Seed = C::Seed;
+
+    fn start(&mut self, input: I) -> Self::Seed {
+        let input = (self.1)(input);
+        self.0.start(input)
+    }
+
+    fn expand_frame(&mut self, seed: Self::Seed) -> F::Frame<Self::Seed> {
+        self.0.expand_frame(seed)
+    }
+}
+
+impl<F: MappableFrame, A: Algebra<F> + ?Sized> Algebra<F> for &mut A {
+    type Carrier = A::Carrier;
+    type Out = A::Out;
+
+    fn collapse_frame(&mut self, frame: F::Frame<Self::Carrier>) -> Self::Carrier {
+        (**self).collapse_frame(frame)
+    }
+
+    fn finish(&mut self, carrier: Self::Carrier) -> Self::Out {
+        (**self).finish(carrier)
+    }
+}
+
+impl<F: MappableFrame, A: Algebra<F> + ?Sized> Algebra<F> for Box<A> {
+    type Carrier = A::Carrier;
+    type Out = A::Out;
+
+    fn collapse_frame(&mut self, frame: F::Frame<Self::Carrier>) -> Self::Carrier {
+        (**self).collapse_frame(frame)
+    }
+
+    fn finish(&mut self, carrier: Self::Carrier) -> Self::Out {
+        (**self).finish(carrier)
+    }
+}
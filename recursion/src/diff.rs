@@ -0,0 +1,518 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::frame::{DiffableFrame, FoldableFrame, MappableFrame};
+use crate::recursive::{collapse::Collapsible, expand::Expandable};
+
+/// A single step of an edit script, as stored in a [`Diff`].
+///
+/// Edits are listed in pre-order, and each edit other than `Insert` consumes the next subtree (or, for
+/// `Descend`, the next node) of the source.
+pub enum Edit<F: MappableFrame> {
+    /// Keep the next subtree of the source unchanged
+    Keep,
+    /// Drop the next subtree of the source
+    Delete,
+    /// Insert a new subtree, given as its frames in pre-order
+    Insert(Vec<F::Frame<()>>),
+    /// Drop the next subtree of the source, inserting a new subtree (given as its frames in pre-order) in its place
+    Replace(Vec<F::Frame<()>>),
+    /// Edit the next node of the source in place, replacing it with a frame that has the same head.
+    ///
+    /// The edits that follow describe its children, up until all children of the source node
+    /// have been consumed and all children of the new frame have been produced.
+    Descend(F::Frame<()>),
+}
+
+impl<F: MappableFrame> fmt::Debug for Edit<F>
+where
+    F::Frame<()>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Keep => f.write_str("Keep"),
+            Edit::Delete => f.write_str("Delete"),
+            Edit::Insert(frames) => f.debug_tuple("Insert").field(frames).finish(),
+            Edit::Replace(frames) => f.debug_tuple("Replace").field(frames).finish(),
+            Edit::Descend(frame) => f.debug_tuple("Descend").field(frame).finish(),
+        }
+    }
+}
+
+/// An edit script describing how to turn one recursive structure into another, as generated by [`diff`].
+///
+/// The script is a flat list of [`Edit`]s, so building, applying and dropping it is stack safe
+/// no matter how deep the structures involved are.
+pub struct Diff<F: MappableFrame> {
+    edits: Vec<Edit<F>>,
+}
+
+impl<F: MappableFrame> fmt::Debug for Diff<F>
+where
+    F::Frame<()>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.edits).finish()
+    }
+}
+
+impl<F: DiffableFrame> Diff<F> {
+    /// The edits making up this script, in pre-order
+    pub fn edits(&self) -> &[Edit<F>] {
+        &self.edits
+    }
+
+    /// Consume this diff, returning the edits making up this script
+    pub fn into_edits(self) -> Vec<Edit<F>> {
+        self.edits
+    }
+
+    /// Whether this diff leaves its source unchanged
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self.edits.as_slice(), [Edit::Keep])
+    }
+
+    /// Apply this diff to the structure it was generated from, building the target structure frame by frame.
+    ///
+    /// Returns `None` if the edits don't line up with `source`, which can happen when applying a diff
+    /// to some structure other than the one it was generated from. This includes a node edited in place
+    /// with a different number of children than the edits that follow it expect.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use recursion::*;
+    /// # #[derive(Debug, PartialEq)]
+    /// # struct Tree(&'static str, Vec<Tree>);
+    /// # #[derive(Debug)]
+    /// # struct Node<A>(&'static str, Vec<A>);
+    /// # impl MappableFrame for Node<PartiallyApplied> {
+    /// #     type Frame<X> = Node<X>;
+    /// #     fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+    /// #         Node(input.0, input.1.into_iter().map(f).collect())
+    /// #     }
+    /// # }
+    /// # impl FoldableFrame for Node<PartiallyApplied> {
+    /// #     fn fold_frame<A, Acc>(input: &Self::Frame<A>, init: Acc, f: impl FnMut(Acc, &A) -> Acc) -> Acc {
+    /// #         input.1.iter().fold(init, f)
+    /// #     }
+    /// # }
+    /// # impl DiffableFrame for Node<PartiallyApplied> {
+    /// #     fn same_head<A, B>(left: &Self::Frame<A>, right: &Self::Frame<B>) -> bool {
+    /// #         left.0 == right.0
+    /// #     }
+    /// # }
+    /// # impl<'a> Collapsible for &'a Tree {
+    /// #     type FrameToken = Node<PartiallyApplied>;
+    /// #     fn into_frame(self) -> Node<&'a Tree> {
+    /// #         Node(self.0, self.1.iter().collect())
+    /// #     }
+    /// # }
+    /// # impl Expandable for Tree {
+    /// #     type FrameToken = Node<PartiallyApplied>;
+    /// #     fn from_frame(val: Node<Tree>) -> Tree {
+    /// #         Tree(val.0, val.1)
+    /// #     }
+    /// # }
+    /// let leaf = |name| Tree(name, Vec::new());
+    /// let source = Tree("r", vec![Tree("a", vec![leaf("b"), leaf("c")]), leaf("d")]);
+    /// let target = Tree("r", vec![Tree("a", vec![leaf("c")]), leaf("d")]);
+    /// let diff = diff(&source, &target);
+    ///
+    /// // the same nodes in pre-order, but "a" has only one child for the diff to delete and keep
+    /// let other = Tree("r", vec![Tree("a", vec![leaf("b")]), leaf("c"), leaf("d")]);
+    /// assert_eq!(None, diff.apply::<_, Tree>(&other));
+    /// ```
+    pub fn apply<Source, Target>(self, source: Source) -> Option<Target>
+    where
+        Source: Collapsible<FrameToken = F>,
+        Target: Expandable<FrameToken = F>,
+    {
+        let mut source = flatten(source).into_iter();
+        let mut frames = Vec::new();
+        // the number of source children still to be consumed and target children still to be produced
+        // by each node being edited in place, below a single root
+        let mut open: Vec<(usize, usize)> = vec![(1, 1)];
+
+        for edit in self.edits {
+            let (consumes, produces) = match &edit {
+                Edit::Delete => (1, 0),
+                Edit::Insert(_) => (0, 1),
+                Edit::Keep | Edit::Replace(_) | Edit::Descend(_) => (1, 1),
+            };
+            let (sources, targets) = open.last_mut()?;
+            *sources = sources.checked_sub(consumes)?;
+            *targets = targets.checked_sub(produces)?;
+
+            match edit {
+                Edit::Keep => take_subtree::<F>(&mut source, |frame| frames.push(frame))?,
+                Edit::Delete => take_subtree::<F>(&mut source, drop)?,
+                Edit::Insert(subtree) => frames.extend(subtree),
+                Edit::Replace(subtree) => {
+                    take_subtree::<F>(&mut source, drop)?;
+                    frames.extend(subtree);
+                }
+                Edit::Descend(frame) => {
+                    let node = source.next()?;
+                    if !F::same_head(&node, &frame) {
+                        return None;
+                    }
+                    open.push((F::child_count(&node), F::child_count(&frame)));
+                    frames.push(frame);
+                }
+            }
+
+            while open.last() == Some(&(0, 0)) {
+                open.pop();
+            }
+        }
+
+        if !open.is_empty() || source.next().is_some() {
+            return None;
+        }
+
+        build(frames)
+    }
+}
+
+/// Compare two recursive structures, generating an edit script that turns `source` into `target`.
+///
+/// Nodes with the same head (see [`DiffableFrame`]) are edited in place. Their children are aligned
+/// by comparing heads, as a longest common subsequence: children of the source without a counterpart
+/// in the target are deleted, and vice versa. Other nodes are replaced, along with all of their children.
+///
+/// This function is stack safe. Aligning the children of two nodes takes time and space proportional
+/// to the product of their child counts.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// #[derive(Debug, PartialEq)]
+/// struct Tree(&'static str, Vec<Tree>);
+///
+/// #[derive(Debug)]
+/// struct Node<A>(&'static str, Vec<A>);
+///
+/// impl MappableFrame for Node<PartiallyApplied> {
+///     type Frame<X> = Node<X>;
+///
+///     fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+///         Node(input.0, input.1.into_iter().map(f).collect())
+///     }
+/// }
+///
+/// impl FoldableFrame for Node<PartiallyApplied> {
+///     fn fold_frame<A, Acc>(input: &Self::Frame<A>, init: Acc, f: impl FnMut(Acc, &A) -> Acc) -> Acc {
+///         input.1.iter().fold(init, f)
+///     }
+/// }
+///
+/// impl DiffableFrame for Node<PartiallyApplied> {
+///     fn same_head<A, B>(left: &Self::Frame<A>, right: &Self::Frame<B>) -> bool {
+///         left.0 == right.0
+///     }
+/// }
+///
+/// impl<'a> Collapsible for &'a Tree {
+///     type FrameToken = Node<PartiallyApplied>;
+///
+///     fn into_frame(self) -> Node<&'a Tree> {
+///         Node(self.0, self.1.iter().collect())
+///     }
+/// }
+///
+/// impl Expandable for Tree {
+///     type FrameToken = Node<PartiallyApplied>;
+///
+///     fn from_frame(val: Node<Tree>) -> Tree {
+///         Tree(val.0, val.1)
+///     }
+/// }
+///
+/// let leaf = |name| Tree(name, Vec::new());
+/// let source = Tree("a", vec![leaf("b"), leaf("c"), leaf("d")]);
+/// let target = Tree("a", vec![leaf("b"), leaf("x"), leaf("d"), leaf("e")]);
+///
+/// let diff = diff(&source, &target);
+/// assert!(matches!(
+///     diff.edits(),
+///     [Edit::Descend(_), Edit::Keep, Edit::Replace(_), Edit::Keep, Edit::Insert(_)]
+/// ));
+///
+/// let patched: Tree = diff.apply(&source).unwrap();
+/// assert_eq!(patched, target);
+/// ```
+pub fn diff<Source, Target>(source: Source, target: Target) -> Diff<Source::FrameToken>
+where
+    Source: Collapsible,
+    Target: Collapsible<FrameToken = Source::FrameToken>,
+    Source::FrameToken: DiffableFrame,
+{
+    let source = flatten(source);
+    let target = flatten(target);
+    let source_sizes = subtree_sizes::<Source::FrameToken>(&source);
+    let target_sizes = subtree_sizes::<Source::FrameToken>(&target);
+    let mut target: Vec<_> = target.into_iter().map(Some).collect();
+
+    let mut script = Script::default();
+    let mut tasks = Vec::new();
+    tasks.push(Task::Diff(0, 0));
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            Task::Diff(s, t) => {
+                let frame = target[t].as_ref().expect("target node visited twice");
+                if !Source::FrameToken::same_head(&source[s], frame) {
+                    let subtree = take_target(&mut target, t, target_sizes[t]);
+                    script.push(Edit::Replace(subtree), 0);
+                    continue;
+                }
+
+                let source_children =
+                    children::<Source::FrameToken, _>(&source[s], s, &source_sizes);
+                let target_children = children::<Source::FrameToken, _>(frame, t, &target_sizes);
+                let aligned = align::<Source::FrameToken>(
+                    &source,
+                    &source_children,
+                    &target,
+                    &target_children,
+                );
+
+                let frame = target[t].take().expect("target node visited twice");
+                script.push(Edit::Descend(frame), source_children.len());
+                tasks.extend(aligned.into_iter().rev());
+            }
+            Task::Delete => script.push(Edit::Delete, 0),
+            Task::Insert(t) => {
+                let subtree = take_target(&mut target, t, target_sizes[t]);
+                script.push(Edit::Insert(subtree), 0);
+            }
+        }
+    }
+
+    Diff {
+        edits: script.edits,
+    }
+}
+
+/// Pending work while generating a diff, in terms of pre-order node indices
+enum Task {
+    Diff(usize, usize),
+    Delete,
+    Insert(usize),
+}
+
+/// An edit script under construction, collapsing any edited node whose children were all kept into a single `Keep`
+struct Script<F: MappableFrame> {
+    edits: Vec<Edit<F>>,
+    open: Vec<OpenNode>,
+}
+
+/// A node that is being edited in place, along with the number of source and target children still to come
+struct OpenNode {
+    start: usize,
+    sources: usize,
+    targets: usize,
+    unchanged: bool,
+}
+
+impl<F: MappableFrame> Default for Script<F> {
+    fn default() -> Self {
+        Self {
+            edits: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+}
+
+impl<F: FoldableFrame> Script<F> {
+    fn push(&mut self, edit: Edit<F>, source_children: usize) {
+        if let Some(parent) = self.open.last_mut() {
+            let (consumes, produces) = match &edit {
+                Edit::Delete => (1, 0),
+                Edit::Insert(_) => (0, 1),
+                Edit::Keep | Edit::Replace(_) | Edit::Descend(_) => (1, 1),
+            };
+            parent.sources -= consumes;
+            parent.targets -= produces;
+            // nodes edited in place are checked once all of their children are known
+            parent.unchanged &= matches!(edit, Edit::Keep | Edit::Descend(_));
+        }
+
+        if let Edit::Descend(frame) = &edit {
+            self.open.push(OpenNode {
+                start: self.edits.len(),
+                sources: source_children,
+                targets: F::child_count(frame),
+                unchanged: true,
+            });
+        }
+        self.edits.push(edit);
+
+        while let Some(node) = self.open.last() {
+            if node.sources > 0 || node.targets > 0 {
+                break;
+            }
+            let node = self.open.pop().unwrap();
+            if node.unchanged {
+                self.edits.truncate(node.start);
+                self.edits.push(Edit::Keep);
+            } else if let Some(parent) = self.open.last_mut() {
+                parent.unchanged = false;
+            }
+        }
+    }
+}
+
+/// Align the children of two nodes with the same head, matching up children with the same head
+/// as a longest common subsequence. Unmatched children between two matches are paired up where possible.
+fn align<F: DiffableFrame>(
+    source: &[F::Frame<()>],
+    source_children: &[usize],
+    target: &[Option<F::Frame<()>>],
+    target_children: &[usize],
+) -> Vec<Task> {
+    let same_head = |s: usize, t: usize| {
+        let frame = target[t].as_ref().expect("target node visited twice");
+        F::same_head(&source[s], frame)
+    };
+
+    // lcs[i * width + j] is the length of the longest common subsequence of
+    // source_children[i..] and target_children[j..]
+    let (m, n) = (source_children.len(), target_children.len());
+    let width = n + 1;
+    let mut lcs = vec![0usize; (m + 1) * width];
+    for i in (0..m).rev() {
+        for j in (0..n).rev() {
+            lcs[i * width + j] = if same_head(source_children[i], target_children[j]) {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut tasks = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < m && j < n {
+        let (s, t) = (source_children[i], target_children[j]);
+        if same_head(s, t) && lcs[i * width + j] == lcs[(i + 1) * width + j + 1] + 1 {
+            flush_gap(&mut tasks, &mut deleted, &mut inserted);
+            tasks.push(Task::Diff(s, t));
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            deleted.push(s);
+            i += 1;
+        } else {
+            inserted.push(t);
+            j += 1;
+        }
+    }
+    deleted.extend_from_slice(&source_children[i..]);
+    inserted.extend_from_slice(&target_children[j..]);
+    flush_gap(&mut tasks, &mut deleted, &mut inserted);
+
+    tasks
+}
+
+fn flush_gap(tasks: &mut Vec<Task>, deleted: &mut Vec<usize>, inserted: &mut Vec<usize>) {
+    let paired = deleted.len().min(inserted.len());
+    tasks.extend((0..paired).map(|k| Task::Diff(deleted[k], inserted[k])));
+    tasks.extend(deleted[paired..].iter().map(|_| Task::Delete));
+    tasks.extend(inserted[paired..].iter().map(|&t| Task::Insert(t)));
+    deleted.clear();
+    inserted.clear();
+}
+
+/// Flatten some structure into its frames, in pre-order
+fn flatten<C: Collapsible>(input: C) -> Vec<<C::FrameToken as MappableFrame>::Frame<()>> {
+    let mut frames = Vec::new();
+    let mut stack = Vec::new();
+    let mut children = Vec::new();
+    stack.push(input);
+
+    while let Some(node) = stack.pop() {
+        frames.push(C::FrameToken::map_frame(node.into_frame(), |child| {
+            children.push(child)
+        }));
+        stack.extend(children.drain(..).rev());
+    }
+
+    frames
+}
+
+/// The size of the subtree rooted at each node of some pre-order list of frames
+fn subtree_sizes<F: FoldableFrame>(frames: &[F::Frame<()>]) -> Vec<usize> {
+    let mut sizes = vec![0; frames.len()];
+    let mut stack = Vec::new();
+
+    for (idx, frame) in frames.iter().enumerate().rev() {
+        let mut size = 1;
+        for _ in 0..F::child_count(frame) {
+            size += stack.pop().expect("malformed pre-order frames");
+        }
+        sizes[idx] = size;
+        stack.push(size);
+    }
+
+    sizes
+}
+
+/// The pre-order indices of the children of the node at `idx`
+fn children<F: FoldableFrame, A>(frame: &F::Frame<A>, idx: usize, sizes: &[usize]) -> Vec<usize> {
+    let mut next = idx + 1;
+    (0..F::child_count(frame))
+        .map(|_| {
+            let child = next;
+            next += sizes[child];
+            child
+        })
+        .collect()
+}
+
+fn take_target<X>(target: &mut [Option<X>], idx: usize, size: usize) -> Vec<X> {
+    target[idx..idx + size]
+        .iter_mut()
+        .map(|frame| frame.take().expect("target node visited twice"))
+        .collect()
+}
+
+/// Take the frames making up the next subtree from some pre-order iterator
+fn take_subtree<F: FoldableFrame>(
+    frames: &mut impl Iterator<Item = F::Frame<()>>,
+    mut f: impl FnMut(F::Frame<()>),
+) -> Option<()> {
+    let mut remaining = 1;
+    while remaining > 0 {
+        let frame = frames.next()?;
+        remaining = remaining - 1 + F::child_count(&frame);
+        f(frame);
+    }
+    Some(())
+}
+
+/// Build some structure from its frames, given in pre-order
+fn build<Out: Expandable>(frames: Vec<<Out::FrameToken as MappableFrame>::Frame<()>>) -> Option<Out>
+where
+    Out::FrameToken: FoldableFrame,
+{
+    let mut stack = Vec::new();
+
+    for frame in frames.into_iter().rev() {
+        if Out::FrameToken::child_count(&frame) > stack.len() {
+            return None;
+        }
+        let frame = Out::FrameToken::map_frame(frame, |()| stack.pop().unwrap());
+        stack.push(Out::from_frame(frame));
+    }
+
+    match (stack.pop(), stack.is_empty()) {
+        (Some(out), true) => Some(out),
+        _ => None,
+    }
+}
@@ -0,0 +1,251 @@
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::ops::Add;
+
+use crate::print::write_line;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Text(Cow<'static, str>),
+    /// A line break, printed as the given text when its enclosing group is flat
+    Line(&'static str),
+    GroupStart,
+    GroupEnd,
+    NestStart(usize),
+    NestEnd,
+}
+
+impl Op {
+    fn flat_width(&self) -> usize {
+        match self {
+            Op::Text(text) => text.chars().count(),
+            Op::Line(flat) => flat.chars().count(),
+            _ => 0,
+        }
+    }
+}
+
+/// A pretty-printing document, in the style of Wadler's "A prettier printer".
+///
+/// Documents are built from [`Doc::text`] and line breaks via concatenation (`+`), with [`Doc::group`]
+/// marking a part of the document to be printed flat, on a single line, if it fits within the target width,
+/// and [`Doc::nest`] increasing the indentation of line breaks that aren't printed flat.
+///
+/// A `Doc` is stored as a flat sequence of operations rather than as a tree, so building, rendering and
+/// dropping a document never recurses. This makes it a ready-made target for
+/// [`crate::CollapsibleExt::collapse_frames`], even for very deep structures.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum ExprFrame<A> {
+/// #     Add(A, A),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// enum Expr {
+///     Add(Box<Expr>, Box<Expr>),
+///     LiteralInt(i64),
+/// }
+///
+/// impl<'a> Collapsible for &'a Expr {
+///     type FrameToken = ExprFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ExprFrame<&'a Expr> {
+///         match self {
+///             Expr::Add(a, b) => ExprFrame::Add(a, b),
+///             Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+///         }
+///     }
+/// }
+///
+/// let lit = |x| Box::new(Expr::LiteralInt(x));
+/// let expr = Expr::Add(Box::new(Expr::Add(lit(1), lit(2))), lit(3));
+///
+/// let doc = (&expr).collapse_frames(|frame| match frame {
+///     ExprFrame::Add(a, b) => {
+///         let args = (Doc::softline() + a + Doc::text(",") + Doc::line() + b).nest(2);
+///         (Doc::text("add(") + args + Doc::softline() + Doc::text(")")).group()
+///     }
+///     ExprFrame::LiteralInt(x) => Doc::text(x.to_string()),
+/// });
+///
+/// assert_eq!(doc.render(80), "add(add(1, 2), 3)");
+/// assert_eq!(doc.render(16), "add(\n  add(1, 2),\n  3\n)");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Doc {
+    ops: VecDeque<Op>,
+}
+
+impl Doc {
+    /// The empty document
+    pub fn nil() -> Self {
+        Self::default()
+    }
+
+    /// Some text, which should not contain newlines
+    pub fn text(text: impl Into<Cow<'static, str>>) -> Self {
+        Self::op(Op::Text(text.into()))
+    }
+
+    /// A line break, printed as a single space when its enclosing group is flat
+    pub fn line() -> Self {
+        Self::op(Op::Line(" "))
+    }
+
+    /// A line break, printed as nothing at all when its enclosing group is flat
+    pub fn softline() -> Self {
+        Self::op(Op::Line(""))
+    }
+
+    /// Concatenate two documents, as with `+`
+    pub fn concat(mut self, mut other: Doc) -> Self {
+        // move the smaller document's operations into the larger document, such that building a document
+        // frame by frame takes O(n log n) moves at worst
+        if self.ops.len() >= other.ops.len() {
+            self.ops.extend(other.ops);
+            self
+        } else {
+            while let Some(op) = self.ops.pop_back() {
+                other.ops.push_front(op);
+            }
+            other
+        }
+    }
+
+    /// Print this document flat if it fits on the current line, otherwise print its line breaks as newlines.
+    /// Groups within a group are only printed flat if their enclosing group is, or if they fit on their own.
+    pub fn group(self) -> Self {
+        self.wrap(Op::GroupStart, Op::GroupEnd)
+    }
+
+    /// Indent any line breaks in this document that aren't printed flat by an additional `indent` spaces
+    pub fn nest(self, indent: usize) -> Self {
+        self.wrap(Op::NestStart(indent), Op::NestEnd)
+    }
+
+    /// Render this document to a new `String`, fitting it within `width` columns where possible
+    pub fn render(&self, width: usize) -> String {
+        let mut out = String::new();
+        self.render_to(width, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Render this document to any [`fmt::Write`] implementation, such as a [`fmt::Formatter`],
+    /// fitting it within `width` columns where possible
+    pub fn render_to<W: Write + ?Sized>(&self, width: usize, out: &mut W) -> fmt::Result {
+        let fits = self.group_widths();
+
+        let mut column = 0;
+        let mut indents = vec![0];
+        let mut flat = vec![false];
+
+        for (idx, op) in self.ops.iter().enumerate() {
+            match op {
+                Op::Text(text) => {
+                    out.write_str(text)?;
+                    column += op.flat_width();
+                }
+                Op::Line(text) if *flat.last().unwrap() => {
+                    out.write_str(text)?;
+                    column += op.flat_width();
+                }
+                Op::Line(_) => {
+                    let indent = *indents.last().unwrap();
+                    write_line(out, indent)?;
+                    column = indent;
+                }
+                Op::GroupStart => {
+                    let parent_flat = *flat.last().unwrap();
+                    flat.push(parent_flat || column + fits[idx] <= width);
+                }
+                Op::GroupEnd => {
+                    flat.pop();
+                }
+                Op::NestStart(indent) => indents.push(indents.last().unwrap() + indent),
+                Op::NestEnd => {
+                    indents.pop();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// For each group start, the width needed to print that group flat: the group itself, along with
+    /// any text that follows it up until the next line break that will be printed as a newline. As in
+    /// Wadler's `fits`, groups that follow are measured flat, while line breaks outside of them are
+    /// in an enclosing group that's already been broken.
+    fn group_widths(&self) -> Vec<usize> {
+        let len = self.ops.len();
+
+        // the offset of each op within the document printed flat, and the end of each group
+        let mut offsets = Vec::with_capacity(len + 1);
+        let mut ends = vec![0; len];
+        let mut starts = Vec::new();
+        let mut offset = 0;
+        for (idx, op) in self.ops.iter().enumerate() {
+            offsets.push(offset);
+            match op {
+                Op::GroupStart => starts.push(idx),
+                Op::GroupEnd => ends[starts.pop().expect("unbalanced group")] = idx,
+                _ => {}
+            }
+            offset += op.flat_width();
+        }
+        offsets.push(offset);
+
+        // the width from each op up until the next line break outside of any group starting after it
+        let mut trailing = vec![0; len + 1];
+        for (idx, op) in self.ops.iter().enumerate().rev() {
+            trailing[idx] = match op {
+                Op::Line(_) => 0,
+                Op::GroupStart => offsets[ends[idx] + 1] - offsets[idx] + trailing[ends[idx] + 1],
+                _ => op.flat_width() + trailing[idx + 1],
+            };
+        }
+
+        trailing
+    }
+
+    fn op(op: Op) -> Self {
+        let mut ops = VecDeque::new();
+        ops.push_back(op);
+        Self { ops }
+    }
+
+    fn wrap(mut self, start: Op, end: Op) -> Self {
+        self.ops.push_front(start);
+        self.ops.push_back(end);
+        self
+    }
+}
+
+impl Add for Doc {
+    type Output = Doc;
+
+    fn add(self, other: Doc) -> Doc {
+        self.concat(other)
+    }
+}
+
+impl FromIterator<Doc> for Doc {
+    fn from_iter<I: IntoIterator<Item = Doc>>(docs: I) -> Self {
+        docs.into_iter().fold(Doc::nil(), Doc::concat)
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::Collapsible;
+
+/// Drop some recursive structure frame by frame, without recursing.
+///
+/// The drop glue generated for a recursive type such as `enum Expr { Add(Box<Expr>, Box<Expr>), .. }`
+/// recurses once per level of nesting, and can overflow the stack when dropping deeply nested values.
+/// Instead, this function takes each frame apart in turn, keeping a stack of the children that are
+/// still to be dropped on the heap.
+///
+/// For types that implement `Drop` (and so can't be taken apart by moving out of their fields), see
+/// [`drop_frames_in_place`] and [`crate::impl_drop_frames`].
+pub fn drop_frames<C: Collapsible>(input: C) {
+    let mut stack = Vec::new();
+    stack.push(input);
+
+    while let Some(node) = stack.pop() {
+        C::FrameToken::map_frame(node.into_frame(), |child| stack.push(child));
+    }
+}
+
+/// Drop the children of some recursive structure frame by frame, without recursing. Intended for
+/// use in `Drop` implementations, see [`crate::impl_drop_frames`].
+///
+/// Children are taken out of each node via its `&mut T` [`Collapsible`] instance, replacing them with
+/// placeholder values from `leaf`, which should be cheap to create and have no children. Children for
+/// which `is_leaf` returns true are left in place, as they can be dropped without recursing. This leaves
+/// `node` itself with only leaf children.
+///
+/// Each node taken apart here is then dropped with only leaf children, so if `T` implements `Drop` via
+/// this function, that nested call neither creates placeholders nor allocates.
+pub fn drop_frames_in_place<T, F>(
+    node: &mut T,
+    mut leaf: impl FnMut() -> T,
+    is_leaf: impl Fn(&T) -> bool,
+) where
+    for<'a> &'a mut T: Collapsible<FrameToken = F>,
+    F: MappableFrame,
+{
+    let mut stack = Vec::new();
+    take_children(node, &mut leaf, &is_leaf, &mut stack);
+
+    while let Some(mut node) = stack.pop() {
+        // with its children moved onto the stack, dropping each node here doesn't recurse
+        take_children(&mut node, &mut leaf, &is_leaf, &mut stack);
+    }
+}
+
+fn take_children<T, F>(
+    node: &mut T,
+    leaf: &mut impl FnMut() -> T,
+    is_leaf: &impl Fn(&T) -> bool,
+    stack: &mut Vec<T>,
+) where
+    for<'a> &'a mut T: Collapsible<FrameToken = F>,
+    F: MappableFrame,
+{
+    F::map_frame(node.into_frame(), |child: &mut T| {
+        if !is_leaf(child) {
+            stack.push(mem::replace(child, leaf()))
+        }
+    });
+}
+
+/// Implement `Drop` for a type via [`drop_frames_in_place`], such that dropping deeply nested values
+/// can't overflow the stack. Takes the type, which must have a `&mut T` [`Collapsible`] instance,
+/// an expression for a placeholder value with no children, and a pattern matching values with no
+/// children (including the placeholder).
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum ListFrame<A> {
+/// #     Cons(u8, A),
+/// #     Nil,
+/// # }
+/// # impl MappableFrame for ListFrame<PartiallyApplied> {
+/// #     type Frame<X> = ListFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ListFrame::Cons(x, next) => ListFrame::Cons(x, f(next)),
+/// #             ListFrame::Nil => ListFrame::Nil,
+/// #         }
+/// #     }
+/// # }
+/// enum List {
+///     Cons(u8, Box<List>),
+///     Nil,
+/// }
+///
+/// impl<'a> Collapsible for &'a mut List {
+///     type FrameToken = ListFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ListFrame<&'a mut List> {
+///         match self {
+///             List::Cons(x, next) => ListFrame::Cons(*x, next),
+///             List::Nil => ListFrame::Nil,
+///         }
+///     }
+/// }
+///
+/// recursion::impl_drop_frames!(List, List::Nil, List::Nil);
+///
+/// let list = (0..1_000_000).fold(List::Nil, |acc, _| List::Cons(0, Box::new(acc)));
+/// drop(list);
+/// ```
+#[macro_export]
+macro_rules! impl_drop_frames {
+    ($ty:ty, $leaf:expr, $is_leaf:pat $(,)?) => {
+        impl ::core::ops::Drop for $ty {
+            fn drop(&mut self) {
+                $crate::drop_frames_in_place(
+                    self,
+                    || $leaf,
+                    |node| ::core::matches!(node, $is_leaf),
+                )
+            }
+        }
+    };
+}
@@ -0,0 +1,684 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use std::collections::{HashMap, HashSet};
+
+use crate::algebra::Algebra;
+use crate::frame::{expand_and_collapse, MappableFrame};
+use crate::recursive::collapse::{Collapsible, CollapsibleExt};
+use crate::recursive::expand::{Expandable, ExpandableExt};
+
+/// Identifies an e-class within an [`EGraph`]. Merging e-classes leaves all but one of their ids
+/// non-canonical, use [`EGraph::find`] to get the canonical id of some e-class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(usize);
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "e{}", self.0)
+    }
+}
+
+/// A term to be added to an [`EGraph`], built from frames with existing e-classes as leaves.
+/// Produced by the rules used by a [`Saturator`].
+pub enum Term<F: MappableFrame> {
+    /// An existing e-class
+    Class(Id),
+    /// A single frame, with a term in each child position
+    Node(Box<F::Frame<Term<F>>>),
+}
+
+impl<F: MappableFrame> Term<F> {
+    /// A term with a single frame at its root
+    pub fn node(frame: F::Frame<Term<F>>) -> Self {
+        Term::Node(Box::new(frame))
+    }
+}
+
+/// An e-node: a frame with its child positions split out, such that only `Frame<()>` need be hashable
+struct ENode<F: MappableFrame> {
+    shape: F::Frame<()>,
+    children: Vec<Id>,
+}
+
+impl<F: MappableFrame> ENode<F> {
+    fn split(frame: F::Frame<Id>) -> Self {
+        let mut children = Vec::new();
+        let shape = F::map_frame(frame, |child| children.push(child));
+        ENode { shape, children }
+    }
+}
+
+impl<F: MappableFrame> ENode<F>
+where
+    F::Frame<()>: Clone,
+{
+    fn join<X>(&self, children: impl IntoIterator<Item = X>) -> F::Frame<X> {
+        let mut children = children.into_iter();
+        F::map_frame(self.shape.clone(), |()| {
+            children
+                .next()
+                .expect("map_frame visited a different number of positions")
+        })
+    }
+}
+
+impl<F: MappableFrame> Clone for ENode<F>
+where
+    F::Frame<()>: Clone,
+{
+    fn clone(&self) -> Self {
+        ENode {
+            shape: self.shape.clone(),
+            children: self.children.clone(),
+        }
+    }
+}
+
+impl<F: MappableFrame> PartialEq for ENode<F>
+where
+    F::Frame<()>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape && self.children == other.children
+    }
+}
+
+impl<F: MappableFrame> Eq for ENode<F> where F::Frame<()>: Eq {}
+
+impl<F: MappableFrame> Hash for ENode<F>
+where
+    F::Frame<()>: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.shape.hash(state);
+        self.children.hash(state);
+    }
+}
+
+struct EClass<F: MappableFrame> {
+    nodes: Vec<ENode<F>>,
+    /// e-nodes with this class as a child, along with the class each belongs to
+    parents: Vec<(ENode<F>, Id)>,
+}
+
+/// An e-graph: a compact representation of many equivalent terms, as used for equality saturation.
+///
+/// Terms of any [`Collapsible`] type are added frame by frame, with each distinct frame of e-class ids stored
+/// once as an e-node. E-classes are sets of e-nodes known to be equivalent, and are merged either directly via
+/// [`EGraph::union`] or by running rewrite rules to saturation via a [`Saturator`]. The best representative
+/// of some e-class can then be extracted into any [`Expandable`] type using a cost algebra.
+///
+/// E-nodes are hashed via their shape, `Frame<()>`, and their child ids, so the frame type need not be
+/// hashable for any other type of child.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # enum ExprFrame<A> {
+/// #     Add(A, A),
+/// #     Mul(A, A),
+/// #     Var(&'static str),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+/// #             ExprFrame::Mul(a, b) => ExprFrame::Mul(f(a), f(b)),
+/// #             ExprFrame::Var(v) => ExprFrame::Var(v),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// # #[derive(Debug, Clone, PartialEq)]
+/// # struct Expr(Box<ExprFrame<Expr>>);
+/// # impl Collapsible for Expr {
+/// #     type FrameToken = ExprFrame<PartiallyApplied>;
+/// #     fn into_frame(self) -> ExprFrame<Expr> {
+/// #         *self.0
+/// #     }
+/// # }
+/// # impl Expandable for Expr {
+/// #     type FrameToken = ExprFrame<PartiallyApplied>;
+/// #     fn from_frame(frame: ExprFrame<Expr>) -> Expr {
+/// #         Expr(Box::new(frame))
+/// #     }
+/// # }
+/// # fn lit(x: i64) -> Expr { Expr::from_frame(ExprFrame::LiteralInt(x)) }
+/// # fn var(v: &'static str) -> Expr { Expr::from_frame(ExprFrame::Var(v)) }
+/// # fn add(a: Expr, b: Expr) -> Expr { Expr::from_frame(ExprFrame::Add(a, b)) }
+/// # fn mul(a: Expr, b: Expr) -> Expr { Expr::from_frame(ExprFrame::Mul(a, b)) }
+/// type Token = ExprFrame<PartiallyApplied>;
+///
+/// let mut egraph = EGraph::new();
+/// // (1 * x) + 0
+/// let root = egraph.add_term(add(mul(lit(1), var("x")), lit(0)));
+///
+/// let has_literal = |egraph: &EGraph<Token>, class: Id, x: i64| {
+///     egraph.nodes(class).any(|node| node == ExprFrame::LiteralInt(x))
+/// };
+/// let saturation = Saturator::new()
+///     .rule("commute", |_, frame: &ExprFrame<Id>| match frame {
+///         ExprFrame::Add(a, b) => Some(Term::node(ExprFrame::Add(Term::Class(*b), Term::Class(*a)))),
+///         ExprFrame::Mul(a, b) => Some(Term::node(ExprFrame::Mul(Term::Class(*b), Term::Class(*a)))),
+///         _ => None,
+///     })
+///     .rule("plus zero", move |egraph: &EGraph<Token>, frame: &ExprFrame<Id>| match frame {
+///         ExprFrame::Add(a, b) if has_literal(egraph, *b, 0) => Some(Term::Class(*a)),
+///         _ => None,
+///     })
+///     .rule("times one", move |egraph: &EGraph<Token>, frame: &ExprFrame<Id>| match frame {
+///         ExprFrame::Mul(a, b) if has_literal(egraph, *b, 1) => Some(Term::Class(*a)),
+///         _ => None,
+///     })
+///     .run(&mut egraph);
+/// assert_eq!(saturation.stop_reason, StopReason::Saturated);
+///
+/// // the smallest equivalent term
+/// let size = algebra::<Token, usize>(|frame| match frame {
+///     ExprFrame::Add(a, b) | ExprFrame::Mul(a, b) => 1 + a + b,
+///     _ => 1,
+/// });
+/// let (cost, best): (usize, Expr) = egraph.extract(root, size);
+/// assert_eq!(cost, 1);
+/// assert_eq!(best, var("x"));
+/// ```
+pub struct EGraph<F: MappableFrame> {
+    /// union-find over e-class ids, each pointing to its parent (or itself, if canonical)
+    unionfind: Vec<Id>,
+    /// the e-class of each e-node, with canonical children after each rebuild
+    memo: HashMap<ENode<F>, Id>,
+    /// indexed by id, `None` for non-canonical ids
+    classes: Vec<Option<EClass<F>>>,
+    /// e-classes that have been merged since the last rebuild
+    pending: Vec<Id>,
+    node_count: usize,
+}
+
+impl<F> Default for EGraph<F>
+where
+    F: MappableFrame,
+    F::Frame<()>: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> EGraph<F>
+where
+    F: MappableFrame,
+    F::Frame<()>: Clone + Eq + Hash,
+{
+    /// An empty e-graph
+    pub fn new() -> Self {
+        Self {
+            unionfind: Vec::new(),
+            memo: HashMap::new(),
+            classes: Vec::new(),
+            pending: Vec::new(),
+            node_count: 0,
+        }
+    }
+
+    /// The number of e-classes
+    pub fn class_count(&self) -> usize {
+        self.classes.iter().flatten().count()
+    }
+
+    /// The number of e-nodes. This may include duplicates until the next [`EGraph::rebuild`].
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// The canonical id of each e-class
+    pub fn classes(&self) -> impl Iterator<Item = Id> + '_ {
+        self.classes
+            .iter()
+            .enumerate()
+            .filter(|(_, class)| class.is_some())
+            .map(|(idx, _)| Id(idx))
+    }
+
+    /// The e-nodes in some e-class, with the canonical id of the e-class in each child position
+    pub fn nodes(&self, id: Id) -> impl Iterator<Item = F::Frame<Id>> + '_ {
+        self.class(id)
+            .nodes
+            .iter()
+            .map(move |node| node.join(node.children.iter().map(|child| self.find(*child))))
+    }
+
+    /// The canonical id of some e-class
+    pub fn find(&self, mut id: Id) -> Id {
+        while self.unionfind[id.0] != id {
+            id = self.unionfind[id.0];
+        }
+        id
+    }
+
+    /// Like [`EGraph::find`], but compresses the path from `id` to its canonical id
+    fn find_mut(&mut self, id: Id) -> Id {
+        let root = self.find(id);
+        let mut id = id;
+        while id != root {
+            id = core::mem::replace(&mut self.unionfind[id.0], root);
+        }
+        root
+    }
+
+    fn class(&self, id: Id) -> &EClass<F> {
+        self.classes[self.find(id).0]
+            .as_ref()
+            .expect("canonical ids always have an e-class")
+    }
+
+    fn class_mut(&mut self, id: Id) -> &mut EClass<F> {
+        let id = self.find_mut(id);
+        self.classes[id.0]
+            .as_mut()
+            .expect("canonical ids always have an e-class")
+    }
+
+    fn canonicalize(&mut self, node: &mut ENode<F>) {
+        for child in node.children.iter_mut() {
+            *child = self.find_mut(*child);
+        }
+    }
+
+    /// Look up the e-class containing some e-node, if any
+    pub fn lookup(&self, frame: F::Frame<Id>) -> Option<Id> {
+        let mut node = ENode::split(frame);
+        for child in node.children.iter_mut() {
+            *child = self.find(*child);
+        }
+        self.memo.get(&node).map(|id| self.find(*id))
+    }
+
+    /// Add a single e-node, returning the id of its e-class. If an equal e-node already exists,
+    /// returns the id of its e-class instead of creating a new one.
+    pub fn add(&mut self, frame: F::Frame<Id>) -> Id {
+        let mut node = ENode::split(frame);
+        self.canonicalize(&mut node);
+        if let Some(id) = self.memo.get(&node).copied() {
+            return self.find_mut(id);
+        }
+
+        let id = Id(self.unionfind.len());
+        self.unionfind.push(id);
+        for child in node.children.iter() {
+            self.class_mut(*child).parents.push((node.clone(), id));
+        }
+        self.classes.push(Some(EClass {
+            nodes: vec![node.clone()],
+            parents: Vec::new(),
+        }));
+        self.memo.insert(node, id);
+        self.node_count += 1;
+        id
+    }
+
+    /// Add some term, frame by frame, returning the id of its root's e-class
+    pub fn add_term<T: Collapsible<FrameToken = F>>(&mut self, term: T) -> Id {
+        term.collapse_frames(|frame: F::Frame<Id>| self.add(frame))
+    }
+
+    /// Add a [`Term`], frame by frame, returning the id of its root's e-class
+    pub fn add_expr(&mut self, term: Term<F>) -> Id {
+        expand_and_collapse::<TermToken<F>, Term<F>, Id>(
+            term,
+            |term| match term {
+                Term::Class(id) => TermFrame::Class(id),
+                Term::Node(frame) => TermFrame::Node(*frame),
+            },
+            |frame| match frame {
+                TermFrame::Class(id) => id,
+                TermFrame::Node(frame) => self.add(frame),
+            },
+        )
+    }
+
+    /// Merge two e-classes, returning `false` if they were already the same e-class. Congruence is only
+    /// restored by the next [`EGraph::rebuild`], until which [`EGraph::add`] and [`EGraph::lookup`] may not
+    /// find every equal e-node.
+    pub fn union(&mut self, a: Id, b: Id) -> bool {
+        let (a, b) = (self.find_mut(a), self.find_mut(b));
+        if a == b {
+            return false;
+        }
+
+        // merge the e-class with fewer parents into the other
+        let (root, merged) = if self.class(a).parents.len() < self.class(b).parents.len() {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        self.unionfind[merged.0] = root;
+        let merged = self.classes[merged.0]
+            .take()
+            .expect("canonical ids always have an e-class");
+        let class = self.class_mut(root);
+        class.nodes.extend(merged.nodes);
+        class.parents.extend(merged.parents);
+        self.pending.push(root);
+        true
+    }
+
+    /// Restore congruence after some calls to [`EGraph::union`], merging any e-classes that now contain
+    /// equal e-nodes, and remove any duplicate e-nodes
+    pub fn rebuild(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        while let Some(id) = self.pending.pop() {
+            let parents = core::mem::take(&mut self.class_mut(id).parents);
+            for (node, _) in parents.iter() {
+                self.memo.remove(node);
+            }
+
+            let mut repaired: HashMap<ENode<F>, Id> = HashMap::new();
+            for (mut node, class) in parents {
+                self.canonicalize(&mut node);
+                if let Some(existing) = repaired.get(&node).copied() {
+                    self.union(existing, class);
+                }
+                let class = self.find_mut(class);
+                self.memo.insert(node.clone(), class);
+                repaired.insert(node, class);
+            }
+
+            // the e-class may have been merged with one of its parents
+            self.class_mut(id).parents.extend(repaired);
+        }
+
+        let mut node_count = 0;
+        for idx in 0..self.classes.len() {
+            let Some(mut class) = self.classes[idx].take() else {
+                continue;
+            };
+            let mut seen = HashSet::new();
+            let nodes = core::mem::take(&mut class.nodes);
+            for mut node in nodes {
+                self.canonicalize(&mut node);
+                if seen.insert(node.clone()) {
+                    class.nodes.push(node);
+                }
+            }
+            node_count += class.nodes.len();
+            self.classes[idx] = Some(class);
+        }
+        self.node_count = node_count;
+    }
+
+    /// Run some analysis over every e-class, expressed as an algebra over e-nodes plus a function to merge
+    /// the results for e-nodes in the same e-class. Returns the result for each e-class, keyed by canonical id.
+    ///
+    /// Results are recomputed until none change, so `merge` should be a join: associative, commutative,
+    /// idempotent, and such that repeatedly merging in new values eventually stops changing the result.
+    pub fn analyze<A>(
+        &self,
+        mut analysis: A,
+        mut merge: impl FnMut(A::Carrier, A::Carrier) -> A::Carrier,
+    ) -> HashMap<Id, A::Out>
+    where
+        A: Algebra<F>,
+        A::Carrier: Clone + PartialEq,
+    {
+        let mut data: HashMap<Id, A::Carrier> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for id in self.classes() {
+                for node in self.class(id).nodes.iter() {
+                    let Some(frame) =
+                        self.collapsed_children::<_, A::Carrier>(node, &data, Clone::clone)
+                    else {
+                        continue;
+                    };
+                    let value = analysis.collapse_frame(frame);
+                    let merged = match data.get(&id) {
+                        Some(existing) => merge(existing.clone(), value),
+                        None => value,
+                    };
+                    if data.get(&id) != Some(&merged) {
+                        data.insert(id, merged);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        data.into_iter()
+            .map(|(id, carrier)| (id, analysis.finish(carrier)))
+            .collect()
+    }
+
+    /// Extract the cheapest term in some e-class, as measured by a cost algebra, along with its cost.
+    ///
+    /// The cost of an e-node should be greater than the cost of each of its children, as with any cost
+    /// function that sums the costs of child positions, so that the cheapest term is finite.
+    pub fn extract<A, T>(&self, root: Id, mut cost: A) -> (A::Out, T)
+    where
+        A: Algebra<F>,
+        A::Carrier: Clone + PartialOrd,
+        T: Expandable<FrameToken = F>,
+    {
+        // the cost of each e-class, along with the index of its cheapest e-node
+        let mut best: HashMap<Id, (A::Carrier, usize)> = HashMap::new();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for id in self.classes() {
+                for (idx, node) in self.class(id).nodes.iter().enumerate() {
+                    let Some(frame) =
+                        self.collapsed_children::<_, A::Carrier>(node, &best, |(c, _)| c.clone())
+                    else {
+                        continue;
+                    };
+                    let node_cost = cost.collapse_frame(frame);
+                    if best.get(&id).map_or(true, |(c, _)| node_cost < *c) {
+                        best.insert(id, (node_cost, idx));
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        let root = self.find(root);
+        let root_cost = best[&root].0.clone();
+        let term = T::expand_frames(root, |id| {
+            let node = &self.class(id).nodes[best[&id].1];
+            node.join(node.children.iter().map(|child| self.find(*child)))
+        });
+        (cost.finish(root_cost), term)
+    }
+
+    /// An e-node's frame with the value for each child's e-class, if every child has one
+    fn collapsed_children<V, X>(
+        &self,
+        node: &ENode<F>,
+        values: &HashMap<Id, V>,
+        get: impl Fn(&V) -> X,
+    ) -> Option<F::Frame<X>> {
+        let children = node
+            .children
+            .iter()
+            .map(|child| values.get(&self.find(*child)).map(&get))
+            .collect::<Option<Vec<_>>>()?;
+        Some(node.join(children))
+    }
+}
+
+impl<F: MappableFrame> fmt::Debug for EGraph<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EGraph")
+            .field("classes", &self.classes.iter().flatten().count())
+            .field("nodes", &self.node_count)
+            .finish()
+    }
+}
+
+enum TermFrame<F: MappableFrame, X> {
+    Class(Id),
+    Node(F::Frame<X>),
+}
+
+struct TermToken<F>(PhantomData<F>);
+
+impl<F: MappableFrame> MappableFrame for TermToken<F> {
+    type Frame<X> = TermFrame<F, X>;
+
+    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        match input {
+            TermFrame::Class(id) => TermFrame::Class(id),
+            TermFrame::Node(frame) => TermFrame::Node(F::map_frame(frame, f)),
+        }
+    }
+}
+
+/// Why a [`Saturator`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReason {
+    /// No rule added any e-nodes or merged any e-classes
+    Saturated,
+    /// The e-graph grew past the node limit
+    NodeLimit,
+    /// The iteration limit was reached
+    IterationLimit,
+}
+
+/// The result of a [`Saturator`] run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Saturation {
+    /// The number of iterations run
+    pub iterations: usize,
+    /// Why saturation stopped
+    pub stop_reason: StopReason,
+    /// The name of each rule that fired, along with the number of times it fired
+    pub fired: Vec<(&'static str, usize)>,
+}
+
+type Rule<'r, F> =
+    Box<dyn Fn(&EGraph<F>, &<F as MappableFrame>::Frame<Id>) -> Option<Term<F>> + 'r>;
+
+/// Runs rewrite rules over an [`EGraph`] until saturation, or until a node or iteration limit is reached.
+///
+/// Each rule is a function from an e-node (with the id of an e-class in each child position) to an optional
+/// term equivalent to it, and may inspect the e-graph to check the e-nodes in child e-classes. Each iteration
+/// applies every rule to every e-node, then adds each resulting term and merges it with the matched e-node's
+/// e-class. See [`EGraph`] for an example.
+pub struct Saturator<'r, F: MappableFrame> {
+    rules: Vec<(&'static str, Rule<'r, F>)>,
+    node_limit: usize,
+    iteration_limit: usize,
+}
+
+impl<F> Default for Saturator<'_, F>
+where
+    F: MappableFrame,
+    F::Frame<()>: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'r, F> Saturator<'r, F>
+where
+    F: MappableFrame,
+    F::Frame<()>: Clone + Eq + Hash,
+{
+    /// A saturator with no rules, a node limit of ten thousand and an iteration limit of thirty
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            node_limit: 10_000,
+            iteration_limit: 30,
+        }
+    }
+
+    /// Add a rule. The name is recorded in the result of each run.
+    pub fn rule(
+        mut self,
+        name: &'static str,
+        rule: impl Fn(&EGraph<F>, &F::Frame<Id>) -> Option<Term<F>> + 'r,
+    ) -> Self {
+        self.rules.push((name, Box::new(rule)));
+        self
+    }
+
+    /// Set the number of e-nodes after which no more rules are applied
+    pub fn node_limit(mut self, node_limit: usize) -> Self {
+        self.node_limit = node_limit;
+        self
+    }
+
+    /// Set the maximum number of iterations
+    pub fn iteration_limit(mut self, iteration_limit: usize) -> Self {
+        self.iteration_limit = iteration_limit;
+        self
+    }
+
+    /// Apply rules to some e-graph until saturation or until some limit is reached, leaving it rebuilt
+    pub fn run(&self, egraph: &mut EGraph<F>) -> Saturation {
+        egraph.rebuild();
+        let mut fired = vec![0; self.rules.len()];
+        let mut iterations = 0;
+        let stop_reason = loop {
+            if iterations == self.iteration_limit {
+                break StopReason::IterationLimit;
+            }
+            iterations += 1;
+
+            // find every match before modifying the e-graph
+            let mut matches = Vec::new();
+            for id in egraph.classes() {
+                for node in egraph.nodes(id) {
+                    for (idx, (_, rule)) in self.rules.iter().enumerate() {
+                        if let Some(term) = rule(egraph, &node) {
+                            matches.push((id, idx, term));
+                        }
+                    }
+                }
+            }
+
+            let node_count = egraph.node_count();
+            let mut changed = false;
+            let mut limited = false;
+            for (id, idx, term) in matches {
+                let rewritten = egraph.add_expr(term);
+                changed |= egraph.union(id, rewritten);
+                fired[idx] += 1;
+                if egraph.node_count() > self.node_limit {
+                    limited = true;
+                    break;
+                }
+            }
+            egraph.rebuild();
+
+            if limited {
+                break StopReason::NodeLimit;
+            }
+            if !changed && egraph.node_count() == node_count {
+                break StopReason::Saturated;
+            }
+        };
+
+        Saturation {
+            iterations,
+            stop_reason,
+            fired: self
+                .rules
+                .iter()
+                .zip(fired)
+                .map(|((name, _), count)| (*name, count))
+                .collect(),
+        }
+    }
+}
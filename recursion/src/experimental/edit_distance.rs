@@ -0,0 +1,178 @@
+use crate::frame::{DiffableFrame, FoldableFrame, MappableFrame};
+use crate::recursive::collapse::Collapsible;
+
+use super::compact::Compact;
+
+/// Per-node costs used to compute a tree edit distance, see [`Compact::edit_distance`].
+///
+/// Each node is passed as its frame with child positions erased. Relabelling a node with itself
+/// should cost nothing, and relabelling should never cost more than deleting and then inserting.
+pub trait EditCosts<F: MappableFrame> {
+    /// The cost of deleting some node from the source tree, promoting its children into its place
+    fn delete(&self, node: &F::Frame<()>) -> u64;
+
+    /// The cost of inserting some node into the target tree
+    fn insert(&self, node: &F::Frame<()>) -> u64;
+
+    /// The cost of relabelling some node of the source tree as a node of the target tree
+    fn relabel(&self, from: &F::Frame<()>, to: &F::Frame<()>) -> u64;
+}
+
+/// Unit costs: each insertion and deletion costs 1, as does relabelling a node with a different head
+/// (see [`DiffableFrame::same_head`])
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnitCosts;
+
+impl<F: DiffableFrame> EditCosts<F> for UnitCosts {
+    fn delete(&self, _node: &F::Frame<()>) -> u64 {
+        1
+    }
+
+    fn insert(&self, _node: &F::Frame<()>) -> u64 {
+        1
+    }
+
+    fn relabel(&self, from: &F::Frame<()>, to: &F::Frame<()>) -> u64 {
+        u64::from(!F::same_head(from, to))
+    }
+}
+
+/// Compute the tree edit distance between two [`Collapsible`] values, by way of [`Compact`].
+/// See [`Compact::edit_distance`] for details, including when this returns `None`.
+pub fn tree_edit_distance<A, B>(a: A, b: B, costs: &impl EditCosts<A::FrameToken>) -> Option<u64>
+where
+    A: Collapsible,
+    B: Collapsible<FrameToken = A::FrameToken>,
+    A::FrameToken: FoldableFrame,
+{
+    Compact::new(a).edit_distance(&Compact::new(b), costs)
+}
+
+impl<F: FoldableFrame> Compact<F> {
+    /// Compute the ordered tree edit distance between this tree and `other`: the minimum total cost of
+    /// a sequence of node deletions, insertions and relabellings turning one into the other.
+    ///
+    /// This uses the Zhang–Shasha algorithm over the post-order layout of each tree, with no recursion,
+    /// so it's safe to use on arbitrarily deep trees. It takes `O(n * m * k)` time for trees of `n` and `m`
+    /// nodes, where `k` is bounded by the product of the depths and the numbers of leaves of the two trees.
+    ///
+    /// # Memory
+    ///
+    /// **This is only suitable when at least one of the two trees is small.** As with any exact tree edit
+    /// distance algorithm, it keeps the distance between every pair of subtrees: a table of `8 * n * m` bytes,
+    /// plus scratch space of at most the same size. Comparing a tree of a hundred thousand nodes against one
+    /// of a dozen takes around ten megabytes, but comparing two trees of a hundred thousand nodes would take
+    /// around 160 gigabytes.
+    ///
+    /// Both tables are allocated up front, before any work is done, and this returns `None` if either
+    /// can't be allocated, rather than aborting the process.
+    pub fn edit_distance(&self, other: &Compact<F>, costs: &impl EditCosts<F>) -> Option<u64> {
+        let source = PostOrder::new::<F>(&self.0);
+        let target = PostOrder::new::<F>(&other.0);
+        let (n, m) = (self.0.len(), other.0.len());
+
+        let delete: Vec<u64> = self.0.iter().map(|node| costs.delete(node)).collect();
+        let insert: Vec<u64> = other.0.iter().map(|node| costs.insert(node)).collect();
+
+        // tree_dist[i * m + j] is the distance between the subtrees rooted at nodes i and j
+        let mut tree_dist = try_zeroed(n.checked_mul(m)?)?;
+        // scratch space for the distances between forests within a single pair of keyroot subtrees,
+        // with row and column 0 standing for the empty forest. The pair of roots needs the most space.
+        let mut forest_dist = try_zeroed((n + 1).checked_mul(m + 1)?)?;
+
+        for &i in &source.keyroots {
+            for &j in &target.keyroots {
+                let (li, lj) = (source.leftmost[i], target.leftmost[j]);
+                let width = j - lj + 2;
+                // index into forest_dist for the forests ending just before nodes i1 and j1
+                let at = |i1: usize, j1: usize| (i1 - li) * width + (j1 - lj);
+
+                forest_dist.clear();
+                forest_dist.resize((i - li + 2) * width, 0);
+                for i1 in li..=i {
+                    forest_dist[at(i1 + 1, lj)] = forest_dist[at(i1, lj)] + delete[i1];
+                }
+                for j1 in lj..=j {
+                    forest_dist[at(li, j1 + 1)] = forest_dist[at(li, j1)] + insert[j1];
+                }
+
+                for i1 in li..=i {
+                    for j1 in lj..=j {
+                        let deleted = forest_dist[at(i1, j1 + 1)] + delete[i1];
+                        let inserted = forest_dist[at(i1 + 1, j1)] + insert[j1];
+
+                        let dist = if source.leftmost[i1] == li && target.leftmost[j1] == lj {
+                            // both forests are whole trees, so this is the distance between them
+                            let relabelled =
+                                forest_dist[at(i1, j1)] + costs.relabel(&self.0[i1], &other.0[j1]);
+                            let dist = deleted.min(inserted).min(relabelled);
+                            tree_dist[i1 * m + j1] = dist;
+                            dist
+                        } else {
+                            let (l1, l2) = (source.leftmost[i1], target.leftmost[j1]);
+                            let matched = forest_dist[at(l1, l2)] + tree_dist[i1 * m + j1];
+                            deleted.min(inserted).min(matched)
+                        };
+                        forest_dist[at(i1 + 1, j1 + 1)] = dist;
+                    }
+                }
+            }
+        }
+
+        Some(match (n, m) {
+            (0, _) => insert.iter().sum(),
+            (_, 0) => delete.iter().sum(),
+            _ => tree_dist[n * m - 1],
+        })
+    }
+}
+
+/// A vector of `len` zeroes, or `None` if it can't be allocated
+fn try_zeroed(len: usize) -> Option<Vec<u64>> {
+    let mut zeroed = Vec::new();
+    zeroed.try_reserve_exact(len).ok()?;
+    zeroed.resize(len, 0);
+    Some(zeroed)
+}
+
+/// Structural information about a tree in post-order, as used by the Zhang–Shasha algorithm.
+///
+/// `Compact` lists each node after all of its children, but visits children from last to first:
+/// this is the post-order of the mirrored tree. Mirroring both trees preserves their edit distance,
+/// so the mirrored layout can be used as-is.
+struct PostOrder {
+    /// The index of the leftmost leaf descendant of each node
+    leftmost: Vec<usize>,
+    /// Nodes that either are the root or have a left sibling, in increasing order
+    keyroots: Vec<usize>,
+}
+
+impl PostOrder {
+    fn new<F: FoldableFrame>(nodes: &[F::Frame<()>]) -> Self {
+        let mut leftmost = Vec::with_capacity(nodes.len());
+        let mut stack: Vec<usize> = Vec::new();
+
+        for (idx, node) in nodes.iter().enumerate() {
+            let children = F::child_count(node);
+            let mut lowest = idx;
+            for _ in 0..children {
+                lowest = lowest.min(stack.pop().expect("malformed compact tree"));
+            }
+            leftmost.push(lowest);
+            stack.push(lowest);
+        }
+
+        // the keyroot for each leftmost leaf is the highest node sharing it, which comes last in post-order
+        let mut is_keyroot = vec![false; nodes.len()];
+        let mut seen = vec![false; nodes.len()];
+        for idx in (0..nodes.len()).rev() {
+            if !seen[leftmost[idx]] {
+                seen[leftmost[idx]] = true;
+                is_keyroot[idx] = true;
+            }
+        }
+        let keyroots = (0..nodes.len()).filter(|&idx| is_keyroot[idx]).collect();
+
+        Self { leftmost, keyroots }
+    }
+}
@@ -1,14 +1,23 @@
 use std::sync::Arc;
 
-use crate::frame::MappableFrame;
+use crate::frame::{MappableFrame, PairMappableFrame, PartiallyApplied};
 
-use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
+use futures::{
+    future::{self, BoxFuture},
+    stream::FuturesUnordered,
+    FutureExt, StreamExt, TryFutureExt,
+};
 use tokio::sync::{mpsc, oneshot};
 
 pub mod compose;
 
 pub(crate) type Frame<F, X> = <F as MappableFrame>::Frame<X>;
 
+type ExpandFn<'a, F, Seed, E> =
+    Arc<dyn Fn(Seed) -> BoxFuture<'a, Result<Frame<F, Seed>, E>> + Send + Sync + 'a>;
+type CollapseFn<'a, F, Out, E> =
+    Arc<dyn Fn(Frame<F, Out>) -> BoxFuture<'a, Result<Out, E>> + Send + Sync + 'a>;
+
 // mostly just used for Compact (defined over frame, needs to collapse_ref via ref frame)
 pub trait MappableFrameRef: MappableFrame {
     type RefFrameToken<'a>: MappableFrame;
@@ -16,6 +25,8 @@ pub trait MappableFrameRef: MappableFrame {
     fn as_ref<X>(input: &Self::Frame<X>) -> <Self::RefFrameToken<'_> as MappableFrame>::Frame<&X>;
 }
 
+/// The async companion to [`crate::TryMappableFrame`]. Unlike `try_map_frame`, no ordering is guaranteed:
+/// implementations may run the futures for each child position concurrently.
 pub trait AsyncMappableFrame: MappableFrame {
     // NOTE: what does having 'a here mean/imply? should 'a bound be on A/B/E?
     fn map_frame_async<'a, A, B, E>(
@@ -28,6 +39,37 @@ pub trait AsyncMappableFrame: MappableFrame {
         B: Send + 'a;
 }
 
+impl AsyncMappableFrame for Option<PartiallyApplied> {
+    fn map_frame_async<'a, A, B, E>(
+        input: Self::Frame<A>,
+        f: impl Fn(A) -> BoxFuture<'a, Result<B, E>> + Send + Sync + 'a,
+    ) -> BoxFuture<'a, Result<Self::Frame<B>, E>>
+    where
+        E: Send + 'a,
+        A: Send + 'a,
+        B: Send + 'a,
+    {
+        match input {
+            Some(a) => f(a).map_ok(Some).boxed(),
+            None => future::ready(Ok(None)).boxed(),
+        }
+    }
+}
+
+impl AsyncMappableFrame for PairMappableFrame {
+    fn map_frame_async<'a, A, B, E>(
+        input: Self::Frame<A>,
+        f: impl Fn(A) -> BoxFuture<'a, Result<B, E>> + Send + Sync + 'a,
+    ) -> BoxFuture<'a, Result<Self::Frame<B>, E>>
+    where
+        E: Send + 'a,
+        A: Send + 'a,
+        B: Send + 'a,
+    {
+        future::try_join(f(input.0), f(input.1)).boxed()
+    }
+}
+
 pub async fn expand_and_collapse_async<'a, Seed, Out, E, F>(
     seed: Seed,
     expand_frame: impl Fn(Seed) -> BoxFuture<'a, Result<Frame<F, Seed>, E>> + Send + Sync + 'a,
@@ -94,12 +136,8 @@ impl<Seed: Sync + Send, Out: Sync + Send> Step<Seed, Out> {
 
     async fn run<'a, F: AsyncMappableFrame, E: Send + Sync + 'a>(
         self,
-        expand_frame: Arc<
-            dyn Fn(Seed) -> BoxFuture<'a, Result<Frame<F, Seed>, E>> + Send + Sync + 'a,
-        >,
-        collapse_frame: Arc<
-            dyn Fn(Frame<F, Out>) -> BoxFuture<'a, Result<Out, E>> + Send + Sync + 'a,
-        >,
+        expand_frame: ExpandFn<'a, F, Seed, E>,
+        collapse_frame: CollapseFn<'a, F, Out, E>,
     ) -> Result<(), E> {
         // first we expand the seed to a frame of seeds
         let frame = expand_frame(self.seed).await?;
@@ -109,7 +147,7 @@ impl<Seed: Sync + Send, Out: Sync + Send> Step<Seed, Out> {
             async {
                 // for each seed in the frame, enqueue a 'Step' while hanging on to that step's receiver
                 let (step, receiver) = Step::new(seed, self.work_queue.clone());
-                self.work_queue.send(step).await.ok().expect("mpsc error");
+                self.work_queue.send(step).await.expect("mpsc error");
 
                 // wait on that step's receiver, at which point we have an 'Out' value to complete with
                 let recvd: Out = receiver.await.expect("oneshot recv error");
@@ -123,10 +161,9 @@ impl<Seed: Sync + Send, Out: Sync + Send> Step<Seed, Out> {
         let collapsed = collapse_frame(node).await?;
 
         // pass the resulting 'Out' to this step's parent
-        self.completion_sender
-            .send(collapsed)
-            .ok()
-            .expect("oneshot send failure");
+        if self.completion_sender.send(collapsed).is_err() {
+            panic!("oneshot send failure");
+        }
 
         Ok(())
     }
@@ -1,5 +1,7 @@
 use crate::frame::MappableFrame;
 
+pub use crate::frame::{PairMappableFrame, PartiallyApplied, TryMappableFrame};
+
 // mostly just used for Compact (defined over frame, needs to collapse_ref via ref frame)
 pub trait MappableFrameRef: MappableFrame {
     type RefFrameToken<'a>: MappableFrame;
@@ -17,45 +19,17 @@ impl<F1: MappableFrame, F2: MappableFrame> MappableFrame for Compose<F1, F2> {
     }
 }
 
-#[derive(Debug)]
-pub enum PartiallyApplied {}
-
-impl MappableFrame for Option<PartiallyApplied> {
-    type Frame<X> = Option<X>;
-
-    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
-        input.map(f)
-    }
-}
-
-impl<Fst> MappableFrame for (Fst, PartiallyApplied) {
-    type Frame<X> = (Fst, X);
-
-    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
-        (input.0, f(input.1))
+impl<F1: TryMappableFrame, F2: TryMappableFrame> TryMappableFrame for Compose<F1, F2> {
+    fn try_map_frame<A, B, E>(
+        input: Self::Frame<A>,
+        mut f: impl FnMut(A) -> Result<B, E>,
+    ) -> Result<Self::Frame<B>, E> {
+        F1::try_map_frame(input, move |x| F2::try_map_frame(x, &mut f))
     }
 }
 
-pub struct PairMappableFrame;
-
 pub type Paired<F> = Compose<PairMappableFrame, F>;
 
-impl MappableFrame for PairMappableFrame {
-    type Frame<X> = (X, X);
-
-    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
-        (f(input.0), f(input.1))
-    }
-}
-
-pub trait TryMappableFrame: MappableFrame {
-    // NOTE: can I do anything about implicit ordering requirement here?
-    fn try_map_frame<A, B, E>(
-        input: Self::Frame<A>,
-        f: impl FnMut(A) -> Result<B, E>,
-    ) -> Result<Self::Frame<B>, E>;
-}
-
 pub fn try_expand_and_collapse<F: TryMappableFrame, Seed, Out, E>(
     seed: Seed,
     mut expand_frame: impl FnMut(Seed) -> Result<F::Frame<Seed>, E>,
@@ -1,4 +1,5 @@
 pub mod compact;
+pub mod edit_distance;
 pub mod fix;
 pub mod frame;
 pub mod recursive;
@@ -1,3 +1,5 @@
+use crate::machine::TraversalContext;
+
 /// A single 'frame' containing values that can be mapped over via `map_frame`.
 ///
 /// # Motivation
@@ -69,6 +71,259 @@ pub trait MappableFrame {
     fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B>;
 }
 
+/// A [`MappableFrame`] that can be mapped over using a fallible function, short-circuiting on the first error.
+///
+/// # Ordering
+///
+/// Implementations must apply `f` to child positions in the same order as `map_frame`,
+/// and must not apply `f` to any further child positions after it returns an error. This makes
+/// effectful traversals built on this trait deterministic: given the same frame, the same
+/// sequence of calls to `f` is made, and the first error encountered in that order is returned.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::{MappableFrame, PairMappableFrame, PartiallyApplied, TryMappableFrame};
+/// let parsed = PairMappableFrame::try_map_frame(("1", "2"), |s| s.parse::<u8>());
+/// assert_eq!(parsed, Ok((1, 2)));
+///
+/// let mut seen = Vec::new();
+/// let parsed = PairMappableFrame::try_map_frame(("x", "y"), |s| {
+///     seen.push(s);
+///     s.parse::<u8>()
+/// });
+/// assert!(parsed.is_err());
+/// // the second position is never visited
+/// assert_eq!(seen, vec!["x"]);
+///
+/// let halved = Option::<PartiallyApplied>::option_map_frame(Some(4), |n| {
+///     if n % 2 == 0 { Some(n / 2) } else { None }
+/// });
+/// assert_eq!(halved, Some(Some(2)));
+/// ```
+pub trait TryMappableFrame: MappableFrame {
+    /// Apply some fallible function `f` to each element inside a frame, in the same order as `map_frame`,
+    /// returning the first error encountered
+    fn try_map_frame<A, B, E>(
+        input: Self::Frame<A>,
+        f: impl FnMut(A) -> Result<B, E>,
+    ) -> Result<Self::Frame<B>, E>;
+
+    /// Apply some optional function `f` to each element inside a frame, in the same order as `map_frame`,
+    /// returning `None` as soon as `f` does
+    fn option_map_frame<A, B>(
+        input: Self::Frame<A>,
+        mut f: impl FnMut(A) -> Option<B>,
+    ) -> Option<Self::Frame<B>> {
+        Self::try_map_frame(input, |a| f(a).ok_or(())).ok()
+    }
+}
+
+/// A [`MappableFrame`] where two frames of the same shape can be zipped together into a single frame
+/// holding pairs of elements, as used by [`crate::zip_collapse`].
+///
+/// # Implementing this trait
+///
+/// Two frames have the same shape if they're the same variant with the same number of child positions.
+/// Implementations decide whether any non-recursive data must also be equal: if it need not be, the
+/// zipped frame should hold the data from the left frame. If the frames don't have the same shape
+/// they're returned unchanged, so that the mismatch can be reported or otherwise handled.
+///
+/// ```rust
+/// # use recursion::{PartiallyApplied, ZippableFrame};
+/// let zipped = Option::<PartiallyApplied>::zip_frames(Some(1), Some("a"));
+/// assert_eq!(zipped, Ok(Some((1, "a"))));
+///
+/// let mismatch = Option::<PartiallyApplied>::zip_frames(Some(1), None::<&str>);
+/// assert_eq!(mismatch, Err((Some(1), None)));
+/// ```
+pub trait ZippableFrame: MappableFrame {
+    /// Zip two frames of the same shape into a single frame holding pairs of their elements,
+    /// returning both frames unchanged if their shapes differ
+    #[allow(clippy::type_complexity)]
+    fn zip_frames<A, B>(
+        left: Self::Frame<A>,
+        right: Self::Frame<B>,
+    ) -> Result<Self::Frame<(A, B)>, (Self::Frame<A>, Self::Frame<B>)>;
+}
+
+/// A [`MappableFrame`] whose frames hold at most one recursive position, such as a cons list,
+/// a Peano number, or a chain of wrappers around some terminal value.
+///
+/// Implementing this trait is a promise that `map_frame` calls the provided function at most once per frame.
+/// It has no methods: it enables the specialized linear traversals in [`crate::CollapsibleLinearExt`],
+/// which don't need to maintain a stack of frames and pending seeds.
+///
+/// ```rust
+/// # use recursion::{LinearFrame, MappableFrame, PartiallyApplied};
+/// enum ListFrame<Elem, Next> {
+///     Cons(Elem, Next),
+///     Nil,
+/// }
+///
+/// impl<Elem> MappableFrame for ListFrame<Elem, PartiallyApplied> {
+///     type Frame<Next> = ListFrame<Elem, Next>;
+///
+///     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+///         match input {
+///             ListFrame::Cons(elem, next) => ListFrame::Cons(elem, f(next)),
+///             ListFrame::Nil => ListFrame::Nil,
+///         }
+///     }
+/// }
+///
+/// impl<Elem> LinearFrame for ListFrame<Elem, PartiallyApplied> {}
+/// ```
+pub trait LinearFrame: MappableFrame {}
+
+/// A [`MappableFrame`] whose child positions can be inspected by reference, without consuming
+/// and rebuilding the frame as `map_frame` does.
+///
+/// # Implementing this trait
+///
+/// `fold_frame` must visit child positions in the same order as `map_frame`.
+///
+/// ```rust
+/// # use recursion::{FoldableFrame, MappableFrame, PartiallyApplied};
+/// enum TreeFrame<A> {
+///     Leaf(usize),
+///     Node(A, A),
+/// }
+///
+/// impl MappableFrame for TreeFrame<PartiallyApplied> {
+///     type Frame<X> = TreeFrame<X>;
+///
+///     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+///         match input {
+///             TreeFrame::Leaf(x) => TreeFrame::Leaf(x),
+///             TreeFrame::Node(a, b) => TreeFrame::Node(f(a), f(b)),
+///         }
+///     }
+/// }
+///
+/// impl FoldableFrame for TreeFrame<PartiallyApplied> {
+///     fn fold_frame<A, Acc>(
+///         input: &Self::Frame<A>,
+///         init: Acc,
+///         mut f: impl FnMut(Acc, &A) -> Acc,
+///     ) -> Acc {
+///         match input {
+///             TreeFrame::Leaf(_) => init,
+///             TreeFrame::Node(a, b) => {
+///                 let acc = f(init, a);
+///                 f(acc, b)
+///             }
+///         }
+///     }
+/// }
+///
+/// let frame = TreeFrame::Node(1, 2);
+/// assert_eq!(TreeFrame::<PartiallyApplied>::child_count(&frame), 2);
+/// assert_eq!(TreeFrame::<PartiallyApplied>::fold_frame(&frame, 0, |acc, x| acc + x), 3);
+/// ```
+pub trait FoldableFrame: MappableFrame {
+    /// Fold over each child position inside a frame by reference, in the same order as `map_frame`
+    fn fold_frame<A, Acc>(input: &Self::Frame<A>, init: Acc, f: impl FnMut(Acc, &A) -> Acc) -> Acc;
+
+    /// The number of child positions inside a frame
+    fn child_count<A>(input: &Self::Frame<A>) -> usize {
+        Self::fold_frame(input, 0, |n, _| n + 1)
+    }
+
+    /// Apply some function `f` to each child position inside a frame, in the same order as `map_frame`
+    fn for_each_child<A>(input: &Self::Frame<A>, mut f: impl FnMut(&A)) {
+        Self::fold_frame(input, (), |(), a| f(a))
+    }
+}
+
+/// A [`FoldableFrame`] where the non-recursive part of two frames (their "head") can be compared, as
+/// used by [`crate::diff`] to decide whether two nodes can be edited in place.
+///
+/// # Implementing this trait
+///
+/// Two frames have the same head if they're the same variant with equal non-recursive data. For frames
+/// with a variable number of child positions, such as `Vec<A>`, the number of children should be ignored:
+/// the children of nodes with the same head are aligned against each other, so that children can be
+/// inserted or deleted.
+///
+/// ```rust
+/// # use recursion::{DiffableFrame, PartiallyApplied};
+/// assert!(<(char, PartiallyApplied)>::same_head(&('a', 1), &('a', "x")));
+/// assert!(!<(char, PartiallyApplied)>::same_head(&('a', 1), &('b', 1)));
+/// ```
+pub trait DiffableFrame: FoldableFrame {
+    /// Whether two frames have the same head, ignoring their children
+    fn same_head<A, B>(left: &Self::Frame<A>, right: &Self::Frame<B>) -> bool;
+}
+
+/// A [`MappableFrame`] with a non-recursive payload type parameter, such as the element type of a list
+/// or the value stored at each node of a tree, which can be mapped over along with the recursive positions.
+///
+/// Frame tokens fix the payload type, so this trait is implemented for each of them with `WithPayload`
+/// naming the token for the same frame with some other payload type. This allows a list of `&str`
+/// to be turned into a list of `String`, for example, via [`crate::CollapsiblePayloadExt::map_payload`].
+///
+/// ```rust
+/// # use recursion::{BimappableFrame, MappableFrame, PartiallyApplied};
+/// enum ListFrame<Elem, Next> {
+///     Cons(Elem, Next),
+///     Nil,
+/// }
+///
+/// impl<Elem> MappableFrame for ListFrame<Elem, PartiallyApplied> {
+///     type Frame<Next> = ListFrame<Elem, Next>;
+///
+///     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+///         match input {
+///             ListFrame::Cons(elem, next) => ListFrame::Cons(elem, f(next)),
+///             ListFrame::Nil => ListFrame::Nil,
+///         }
+///     }
+/// }
+///
+/// impl<Elem> BimappableFrame for ListFrame<Elem, PartiallyApplied> {
+///     type Payload = Elem;
+///     type WithPayload<P> = ListFrame<P, PartiallyApplied>;
+///
+///     fn bimap_frame<A, B, P>(
+///         input: Self::Frame<A>,
+///         mut payload: impl FnMut(Elem) -> P,
+///         mut f: impl FnMut(A) -> B,
+///     ) -> ListFrame<P, B> {
+///         match input {
+///             ListFrame::Cons(elem, next) => ListFrame::Cons(payload(elem), f(next)),
+///             ListFrame::Nil => ListFrame::Nil,
+///         }
+///     }
+/// }
+///
+/// let frame = ListFrame::Cons("1", 2);
+/// let frame = ListFrame::<_, PartiallyApplied>::bimap_frame(frame, str::len, |n| n * 2);
+/// assert!(matches!(frame, ListFrame::Cons(1, 4)));
+/// ```
+pub trait BimappableFrame: MappableFrame {
+    /// The payload type held by this token's frames
+    type Payload;
+
+    /// The token for the same frame type, holding payloads of type `P` instead
+    type WithPayload<P>: BimappableFrame<Payload = P>;
+
+    /// Apply `payload` to each payload and `f` to each recursive position inside a frame
+    fn bimap_frame<A, B, P>(
+        input: Self::Frame<A>,
+        payload: impl FnMut(Self::Payload) -> P,
+        f: impl FnMut(A) -> B,
+    ) -> <Self::WithPayload<P> as MappableFrame>::Frame<B>;
+
+    /// Apply `payload` to each payload inside a frame, leaving its recursive positions as-is
+    fn map_payload<A, P>(
+        input: Self::Frame<A>,
+        payload: impl FnMut(Self::Payload) -> P,
+    ) -> <Self::WithPayload<P> as MappableFrame>::Frame<A> {
+        Self::bimap_frame(input, payload, |a| a)
+    }
+}
+
 /// "An uninhabited type used to define [`MappableFrame`] instances for partially-applied types."
 ///
 /// For example: the MappableFrame instance for `MyFrame<A>` cannot be written over the
@@ -76,6 +331,161 @@ pub trait MappableFrame {
 #[derive(Debug)]
 pub enum PartiallyApplied {}
 
+impl MappableFrame for Option<PartiallyApplied> {
+    type Frame<X> = Option<X>;
+
+    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        input.map(f)
+    }
+}
+
+impl TryMappableFrame for Option<PartiallyApplied> {
+    fn try_map_frame<A, B, E>(
+        input: Self::Frame<A>,
+        f: impl FnMut(A) -> Result<B, E>,
+    ) -> Result<Self::Frame<B>, E> {
+        input.map(f).transpose()
+    }
+}
+
+impl FoldableFrame for Option<PartiallyApplied> {
+    fn fold_frame<A, Acc>(input: &Self::Frame<A>, init: Acc, f: impl FnMut(Acc, &A) -> Acc) -> Acc {
+        input.iter().fold(init, f)
+    }
+}
+
+impl LinearFrame for Option<PartiallyApplied> {}
+
+impl DiffableFrame for Option<PartiallyApplied> {
+    fn same_head<A, B>(left: &Self::Frame<A>, right: &Self::Frame<B>) -> bool {
+        left.is_some() == right.is_some()
+    }
+}
+
+impl ZippableFrame for Option<PartiallyApplied> {
+    fn zip_frames<A, B>(
+        left: Self::Frame<A>,
+        right: Self::Frame<B>,
+    ) -> Result<Self::Frame<(A, B)>, (Self::Frame<A>, Self::Frame<B>)> {
+        match (left, right) {
+            (Some(a), Some(b)) => Ok(Some((a, b))),
+            (None, None) => Ok(None),
+            mismatch => Err(mismatch),
+        }
+    }
+}
+
+impl<Fst> MappableFrame for (Fst, PartiallyApplied) {
+    type Frame<X> = (Fst, X);
+
+    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        (input.0, f(input.1))
+    }
+}
+
+impl<Fst> TryMappableFrame for (Fst, PartiallyApplied) {
+    fn try_map_frame<A, B, E>(
+        input: Self::Frame<A>,
+        mut f: impl FnMut(A) -> Result<B, E>,
+    ) -> Result<Self::Frame<B>, E> {
+        Ok((input.0, f(input.1)?))
+    }
+}
+
+impl<Fst> FoldableFrame for (Fst, PartiallyApplied) {
+    fn fold_frame<A, Acc>(
+        input: &Self::Frame<A>,
+        init: Acc,
+        mut f: impl FnMut(Acc, &A) -> Acc,
+    ) -> Acc {
+        f(init, &input.1)
+    }
+}
+
+impl<Fst> LinearFrame for (Fst, PartiallyApplied) {}
+
+impl<Fst: PartialEq> DiffableFrame for (Fst, PartiallyApplied) {
+    fn same_head<A, B>(left: &Self::Frame<A>, right: &Self::Frame<B>) -> bool {
+        left.0 == right.0
+    }
+}
+
+impl<Fst> BimappableFrame for (Fst, PartiallyApplied) {
+    type Payload = Fst;
+    type WithPayload<P> = (P, PartiallyApplied);
+
+    fn bimap_frame<A, B, P>(
+        input: Self::Frame<A>,
+        mut payload: impl FnMut(Fst) -> P,
+        mut f: impl FnMut(A) -> B,
+    ) -> (P, B) {
+        (payload(input.0), f(input.1))
+    }
+}
+
+/// Pairs with unequal first elements are considered to have different shapes
+impl<Fst: PartialEq> ZippableFrame for (Fst, PartiallyApplied) {
+    fn zip_frames<A, B>(
+        left: Self::Frame<A>,
+        right: Self::Frame<B>,
+    ) -> Result<Self::Frame<(A, B)>, (Self::Frame<A>, Self::Frame<B>)> {
+        if left.0 == right.0 {
+            Ok((left.0, (left.1, right.1)))
+        } else {
+            Err((left, right))
+        }
+    }
+}
+
+/// A frame token for pairs `(X, X)`, where both positions are mapped over
+#[derive(Debug)]
+pub struct PairMappableFrame;
+
+impl MappableFrame for PairMappableFrame {
+    type Frame<X> = (X, X);
+
+    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        (f(input.0), f(input.1))
+    }
+}
+
+impl TryMappableFrame for PairMappableFrame {
+    fn try_map_frame<A, B, E>(
+        input: Self::Frame<A>,
+        mut f: impl FnMut(A) -> Result<B, E>,
+    ) -> Result<Self::Frame<B>, E> {
+        let fst = f(input.0)?;
+        let snd = f(input.1)?;
+        Ok((fst, snd))
+    }
+}
+
+impl ZippableFrame for PairMappableFrame {
+    fn zip_frames<A, B>(
+        left: Self::Frame<A>,
+        right: Self::Frame<B>,
+    ) -> Result<Self::Frame<(A, B)>, (Self::Frame<A>, Self::Frame<B>)> {
+        Ok(((left.0, right.0), (left.1, right.1)))
+    }
+}
+
+impl FoldableFrame for PairMappableFrame {
+    fn fold_frame<A, Acc>(
+        input: &Self::Frame<A>,
+        init: Acc,
+        mut f: impl FnMut(Acc, &A) -> Acc,
+    ) -> Acc {
+        let acc = f(init, &input.0);
+        f(acc, &input.1)
+    }
+}
+
+impl DiffableFrame for PairMappableFrame {
+    fn same_head<A, B>(_left: &Self::Frame<A>, _right: &Self::Frame<B>) -> bool {
+        true
+    }
+}
+
 /// This function generates a stack machine for some frame `F::Frame`,
 /// expanding some seed value `Seed` into frames via a function `Seed -> Frame<Seed>`
 /// and collapsing those values via a function `Frame<Out> -> Out`.
@@ -87,39 +497,10 @@ pub enum PartiallyApplied {}
 /// susceptible to stack overflows if said stack expands
 pub(crate) fn expand_and_collapse<F: MappableFrame, Seed, Out>(
     seed: Seed,
-    mut expand_frame: impl FnMut(Seed) -> F::Frame<Seed>,
-    mut collapse_frame: impl FnMut(F::Frame<Out>) -> Out,
+    expand_frame: impl FnMut(Seed) -> F::Frame<Seed>,
+    collapse_frame: impl FnMut(F::Frame<Out>) -> Out,
 ) -> Out {
-    enum State<Seed, CollapsibleInternal> {
-        Expand(usize, Seed),
-        Collapse(usize, CollapsibleInternal),
-    }
-
-    let mut vals: Vec<Option<Out>> = vec![None];
-    let mut stack = vec![State::Expand(0, seed)];
-
-    while let Some(item) = stack.pop() {
-        match item {
-            State::Expand(val_idx, seed) => {
-                let node = expand_frame(seed);
-                let mut seeds = Vec::new();
-                let node = F::map_frame(node, |seed| {
-                    vals.push(None);
-                    let idx = vals.len() - 1;
-                    seeds.push(State::Expand(idx, seed));
-                    idx
-                });
-
-                stack.push(State::Collapse(val_idx, node));
-                stack.extend(seeds);
-            }
-            State::Collapse(val_idx, node) => {
-                let node = F::map_frame(node, |k| vals[k].take().unwrap());
-                vals[val_idx] = Some(collapse_frame(node));
-            }
-        };
-    }
-    vals[0].take().unwrap()
+    TraversalContext::<F, Seed, Out>::new().expand_and_collapse(seed, expand_frame, collapse_frame)
 }
 
 /// This function generates a fallible stack machine for some frame `F::Frame`,
@@ -133,37 +514,12 @@ pub(crate) fn expand_and_collapse<F: MappableFrame, Seed, Out>(
 /// susceptible to stack overflows if said stack expands
 pub(crate) fn try_expand_and_collapse<F: MappableFrame, Seed, Out, E>(
     seed: Seed,
-    mut expand_frame: impl FnMut(Seed) -> Result<F::Frame<Seed>, E>,
-    mut collapse_frame: impl FnMut(F::Frame<Out>) -> Result<Out, E>,
+    expand_frame: impl FnMut(Seed) -> Result<F::Frame<Seed>, E>,
+    collapse_frame: impl FnMut(F::Frame<Out>) -> Result<Out, E>,
 ) -> Result<Out, E> {
-    enum State<Seed, CollapsibleInternal> {
-        Expand(usize, Seed),
-        Collapse(usize, CollapsibleInternal),
-    }
-
-    let mut vals: Vec<Option<Out>> = vec![None];
-    let mut stack = vec![State::Expand(0, seed)];
-
-    while let Some(item) = stack.pop() {
-        match item {
-            State::Expand(val_idx, seed) => {
-                let node = expand_frame(seed)?;
-                let mut seeds = Vec::new();
-                let node = F::map_frame(node, |seed| {
-                    vals.push(None);
-                    let idx = vals.len() - 1;
-                    seeds.push(State::Expand(idx, seed));
-                    idx
-                });
-
-                stack.push(State::Collapse(val_idx, node));
-                stack.extend(seeds);
-            }
-            State::Collapse(val_idx, node) => {
-                let node = F::map_frame(node, |k| vals[k].take().unwrap());
-                vals[val_idx] = Some(collapse_frame(node)?);
-            }
-        };
-    }
-    Ok(vals[0].take().unwrap())
+    TraversalContext::<F, Seed, Out>::new().try_expand_and_collapse(
+        seed,
+        expand_frame,
+        collapse_frame,
+    )
 }
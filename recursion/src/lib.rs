@@ -301,6 +301,12 @@ Here's a GIF visualizing the operation of `expand_frames``:
 
 <img src="https://raw.githubusercontent.com/inanna-malick/recursion/84806b5ce8a9e12ef7d1664d031e215922bfbaa6/recursion/img_assets/build_expr.gif" width="600">
 
+# `no_std` support
+
+This crate is `no_std` compatible, requiring only `alloc`: disable the default `std` feature to use it on targets
+without the standard library. Traversals only use heap-allocated stacks, so they're safe to run on devices
+with small call stacks. The `experimental` feature requires `std`.
+
 # Miscellaneous errata
 
 All GIFs in this documentation were generated via tooling in my `recursion-visualize` crate, via `examples/expr.rs`.
@@ -311,11 +317,60 @@ read [`MappableFrame`] as `Functor` and [`Expandable`]/[`Collapsible`] as `Corec
 familiar with these idioms, there's a great blog post series [here](https://blog.sumtypeofway.com/posts/introduction-to-recursion-schemes.html) that explains the various concepts involved.
 
 */
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod algebra;
+mod diff;
+mod doc;
+mod drop;
+#[cfg(feature = "std")]
+mod egraph;
 mod frame;
+mod machine;
+mod metrics;
+mod monoid;
+mod mutual;
+mod path;
+mod print;
 mod recursive;
+mod rewrite;
+mod structural;
+mod visit;
 
 #[cfg(feature = "experimental")]
 pub mod experimental;
 
-pub use frame::{MappableFrame, PartiallyApplied};
-pub use recursive::{Collapsible, CollapsibleExt, Expandable, ExpandableExt};
+pub use algebra::{
+    algebra, coalgebra, try_algebra, Algebra, Coalgebra, Contramap, Fallible, FnAlgebra,
+    FnCoalgebra, IntoAlgebra, IntoCoalgebra, IntoTryAlgebra, Map, TryAlgebra, TryZip, Zip,
+};
+pub use diff::{diff, Diff, Edit};
+pub use doc::Doc;
+pub use drop::{drop_frames, drop_frames_in_place};
+#[cfg(feature = "std")]
+pub use egraph::{EGraph, Id, Saturation, Saturator, StopReason, Term};
+pub use frame::{
+    BimappableFrame, DiffableFrame, FoldableFrame, LinearFrame, MappableFrame, PairMappableFrame,
+    PartiallyApplied, TryMappableFrame, ZippableFrame,
+};
+pub use machine::{
+    Checkpoint, CollapseMachine, CollapsibleStepwiseExt, ExpandMachine, ExpandableStepwiseExt,
+    Progress, StackMachine, TraversalContext,
+};
+pub use metrics::{CollapsibleMetricsExt, TreeMetrics};
+pub use monoid::{CollapsibleMonoidExt, Max, Min, Monoid, Product, Sum};
+pub use mutual::{
+    MutualCollapsible, MutualCollapsibleExt, MutualExpandable, MutualExpandableExt, MutualFrame,
+    Sort,
+};
+pub use path::{CollapsiblePathExt, Label, LabelledFrame, Path, Positional};
+pub use print::{Child, Layout, TreePrinter};
+pub use recursive::{
+    zip_collapse, Collapsible, CollapsibleConvertExt, CollapsibleExt, CollapsibleLinearExt,
+    CollapsiblePayloadExt, Expandable, ExpandableExt,
+};
+pub use rewrite::{LimitExceeded, Rewriter, Rewritten, Strategy};
+pub use structural::{clone_frames, cmp_frames, eq_frames, hash_frames};
+pub use visit::for_each_node_mut;
@@ -0,0 +1,494 @@
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use crate::algebra::{Algebra, Coalgebra, FnAlgebra, FnCoalgebra, IntoAlgebra, IntoCoalgebra};
+use crate::frame::MappableFrame;
+use crate::recursive::{collapse::Collapsible, expand::Expandable};
+
+/// The result of driving a [`StackMachine`] forward: either the traversal is still in progress,
+/// or it has completed with some output value.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Progress<Out> {
+    /// There is still work remaining, call `step` or `run_for` again to continue the traversal
+    Pending,
+    /// The traversal has completed, producing a single output value
+    Done(Out),
+}
+
+impl<Out> Progress<Out> {
+    /// Returns `true` if the traversal has completed
+    pub fn is_done(&self) -> bool {
+        matches!(self, Progress::Done(_))
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum State<Seed, CollapsibleInternal> {
+    Expand(Seed),
+    Collapse(CollapsibleInternal),
+}
+
+/// The in-progress state of a [`StackMachine`]: the pending stack of seeds and partially collapsed
+/// frames, plus any output values that have been computed but not yet consumed by their parent frame.
+///
+/// A checkpoint holds no functions, so it can be persisted (via `serde`, with the `serde` feature enabled,
+/// if `Seed`, `Out` and `F::Frame<()>` are serializable) and later turned back into a running machine
+/// via [`StackMachine::resume`].
+///
+/// A checkpoint must be resumed with the same expand and collapse functions it was created with.
+/// Resuming a checkpoint that was not produced by a [`StackMachine`] may cause the machine to panic.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "Seed: serde::Serialize, Out: serde::Serialize, F::Frame<()>: serde::Serialize",
+        deserialize = "Seed: serde::Deserialize<'de>, Out: serde::Deserialize<'de>, F::Frame<()>: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Checkpoint<F: MappableFrame, Seed, Out> {
+    // outputs of fully collapsed subtrees, in the order they'll be consumed by their parent frames
+    vals: Vec<Out>,
+    // frames are stored with `()` in place of their children: a frame's child outputs
+    // are always found on top of `vals` by the time it is collapsed
+    stack: Vec<State<Seed, F::Frame<()>>>,
+}
+
+impl<F: MappableFrame, Seed, Out> Checkpoint<F, Seed, Out> {
+    /// Returns `true` if there is no remaining work, eg because the machine has already completed
+    pub fn is_complete(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+/// Reusable scratch buffers for the stack machine that drives
+/// [`crate::CollapsibleExt::collapse_frames`] and [`crate::ExpandableExt::expand_frames`].
+///
+/// Each traversal needs a few internal buffers, which are normally allocated fresh for every call.
+/// When running many small traversals in a hot loop, keep a `TraversalContext` around and pass it to
+/// [`crate::CollapsibleExt::collapse_frames_with`] (or the other `*_with` methods) instead:
+/// the buffers are cleared, but not deallocated, after each traversal so that their capacity is reused.
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum IntTree {
+/// #     Leaf { value: usize },
+/// #     Node { left: Box<Self>, right: Box<Self> },
+/// # }
+/// # impl IntTree {
+/// #   fn node(left: Self, right: Self) -> Self { Self::Node{left: Box::new(left), right: Box::new(right)}}
+/// #   fn leaf(value: usize) -> Self { Self::Leaf{value}}
+/// # }
+/// # enum IntTreeFrame<A> {
+/// #     Leaf { value: usize },
+/// #     Node { left: A, right: A },
+/// # }
+/// # impl MappableFrame for IntTreeFrame<PartiallyApplied> {
+/// #    type Frame<X> = IntTreeFrame<X>;
+/// #
+/// #    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             IntTreeFrame::Leaf { value } => IntTreeFrame::Leaf { value },
+/// #             IntTreeFrame::Node { left, right } => IntTreeFrame::Node {
+/// #                 left: f(left),
+/// #                 right: f(right),
+/// #             },
+/// #         }
+/// #     }
+/// # }
+/// # impl<'a> Collapsible for &'a IntTree {
+/// #     type FrameToken = IntTreeFrame<PartiallyApplied>;
+/// #
+/// #     fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+/// #         match self {
+/// #             IntTree::Leaf { value } => IntTreeFrame::Leaf { value: *value },
+/// #             IntTree::Node { left, right } => IntTreeFrame::Node {
+/// #                 left: left.as_ref(),
+/// #                 right: right.as_ref(),
+/// #             },
+/// #         }
+/// #     }
+/// # }
+/// let trees: Vec<IntTree> = (0..100)
+///     .map(|n| IntTree::node(IntTree::leaf(n), IntTree::leaf(1)))
+///     .collect();
+///
+/// let mut ctx = TraversalContext::new();
+///
+/// let sum: usize = trees
+///     .iter()
+///     .map(|tree| {
+///         tree.collapse_frames_with(&mut ctx, |frame| match frame {
+///             IntTreeFrame::Leaf { value } => value,
+///             IntTreeFrame::Node { left, right } => left + right,
+///         })
+///     })
+///     .sum();
+///
+/// assert_eq!(sum, 5050);
+/// ```
+///
+/// Note that the context is typed over the seed and output values of the traversal, so a context
+/// used to collapse borrowed values (eg `&'a IntTree`) can only be reused for values with the same lifetime.
+pub struct TraversalContext<F: MappableFrame, Seed, Out> {
+    state: Checkpoint<F, Seed, Out>,
+}
+
+impl<F: MappableFrame, Seed, Out> TraversalContext<F, Seed, Out> {
+    /// Create a new, empty, context. No buffers are allocated until the context is used.
+    pub fn new() -> Self {
+        Self {
+            state: Checkpoint {
+                vals: Vec::new(),
+                stack: Vec::new(),
+            },
+        }
+    }
+
+    fn start(&mut self, seed: Seed) {
+        self.state.stack.push(State::Expand(seed));
+    }
+
+    fn finish(&mut self) -> Out {
+        self.state.vals.pop().unwrap()
+    }
+
+    fn abort(&mut self) {
+        self.state.vals.clear();
+        self.state.stack.clear();
+    }
+
+    #[inline(always)]
+    fn process<E>(
+        &mut self,
+        item: State<Seed, F::Frame<()>>,
+        expand_frame: &mut impl FnMut(Seed) -> Result<F::Frame<Seed>, E>,
+        collapse_frame: &mut impl FnMut(F::Frame<Out>) -> Result<Out, E>,
+    ) -> Result<(), E> {
+        match item {
+            State::Expand(seed) => {
+                let node = expand_frame(seed)?;
+                let stack = &mut self.state.stack;
+
+                // push child seeds directly onto the stack, then slot the parent frame in beneath them.
+                // The last child is expanded and collapsed first, so by the time the parent frame
+                // is collapsed its child outputs are on top of `vals` with the first child on top.
+                let idx = stack.len();
+                let node = F::map_frame(node, |seed| stack.push(State::Expand(seed)));
+                if idx == stack.len() {
+                    stack.push(State::Collapse(node));
+                } else {
+                    stack.insert(idx, State::Collapse(node));
+                }
+            }
+            State::Collapse(node) => {
+                let vals = &mut self.state.vals;
+                let node = F::map_frame(node, |_: ()| vals.pop().unwrap());
+                vals.push(collapse_frame(node)?);
+            }
+        };
+        Ok(())
+    }
+
+    /// Run a complete traversal using the buffers owned by this context,
+    /// leaving them empty (but with their capacity intact) afterwards
+    pub(crate) fn try_expand_and_collapse<E>(
+        &mut self,
+        seed: Seed,
+        mut expand_frame: impl FnMut(Seed) -> Result<F::Frame<Seed>, E>,
+        mut collapse_frame: impl FnMut(F::Frame<Out>) -> Result<Out, E>,
+    ) -> Result<Out, E> {
+        self.start(seed);
+        while let Some(item) = self.state.stack.pop() {
+            if let Err(e) = self.process(item, &mut expand_frame, &mut collapse_frame) {
+                self.abort();
+                return Err(e);
+            }
+        }
+        Ok(self.finish())
+    }
+
+    /// Run a complete traversal using the buffers owned by this context,
+    /// leaving them empty (but with their capacity intact) afterwards
+    pub(crate) fn expand_and_collapse(
+        &mut self,
+        seed: Seed,
+        mut expand_frame: impl FnMut(Seed) -> F::Frame<Seed>,
+        mut collapse_frame: impl FnMut(F::Frame<Out>) -> Out,
+    ) -> Out {
+        match self.try_expand_and_collapse::<Infallible>(
+            seed,
+            |seed| Ok(expand_frame(seed)),
+            |node| Ok(collapse_frame(node)),
+        ) {
+            Ok(out) => out,
+            Err(e) => match e {},
+        }
+    }
+}
+
+impl<F: MappableFrame, Seed, Out> Default for TraversalContext<F, Seed, Out> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A stack machine for some frame `F::Frame`, expanding seeds into frames via some [`Coalgebra`] and
+/// collapsing those frames via some [`Algebra`], reified as a value that can be driven forward one step
+/// at a time. Closures are accepted wherever an algebra or coalgebra is, as with
+/// [`crate::CollapsibleExt::collapse_frames`].
+///
+/// This is the same depth-first traversal used by [`crate::CollapsibleExt::collapse_frames`]
+/// and [`crate::ExpandableExt::expand_frames`], but instead of running to completion in a single call
+/// the caller decides how much work to do at a time via [`StackMachine::step`] and [`StackMachine::run_for`].
+/// This makes it possible to interleave a large traversal with other work (eg, in an event loop)
+/// without using threads or async.
+///
+/// Each step either expands a single seed into a frame or collapses a single frame into an output value.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # #[derive(Debug, PartialEq, Eq)]
+/// # enum IntTree {
+/// #     Leaf { value: usize },
+/// #     Node { left: Box<Self>, right: Box<Self> },
+/// # }
+/// # impl IntTree {
+/// #   fn node(left: Self, right: Self) -> Self { Self::Node{left: Box::new(left), right: Box::new(right)}}
+/// #   fn leaf(value: usize) -> Self { Self::Leaf{value}}
+/// # }
+/// # enum IntTreeFrame<A> {
+/// #     Leaf { value: usize },
+/// #     Node { left: A, right: A },
+/// # }
+/// # impl MappableFrame for IntTreeFrame<PartiallyApplied> {
+/// #    type Frame<X> = IntTreeFrame<X>;
+/// #
+/// #    fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             IntTreeFrame::Leaf { value } => IntTreeFrame::Leaf { value },
+/// #             IntTreeFrame::Node { left, right } => IntTreeFrame::Node {
+/// #                 left: f(left),
+/// #                 right: f(right),
+/// #             },
+/// #         }
+/// #     }
+/// # }
+/// # impl<'a> Collapsible for &'a IntTree {
+/// #     type FrameToken = IntTreeFrame<PartiallyApplied>;
+/// #
+/// #     fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+/// #         match self {
+/// #             IntTree::Leaf { value } => IntTreeFrame::Leaf { value: *value },
+/// #             IntTree::Node { left, right } => IntTreeFrame::Node {
+/// #                 left: left.as_ref(),
+/// #                 right: right.as_ref(),
+/// #             },
+/// #         }
+/// #     }
+/// # }
+/// let tree = IntTree::node(
+///     IntTree::node(IntTree::leaf(1), IntTree::leaf(2)),
+///     IntTree::node(IntTree::leaf(3), IntTree::leaf(4)),
+/// );
+///
+/// let mut machine = (&tree).collapse_frames_stepwise(|frame| match frame {
+///     IntTreeFrame::Leaf { value } => value,
+///     IntTreeFrame::Node { left, right } => left + right,
+/// });
+///
+/// // do a bit of work, then yield to the caller
+/// assert_eq!(machine.run_for(3), Progress::Pending);
+///
+/// // and later, pick up where we left off
+/// let mut ticks = 0;
+/// let sum = loop {
+///     ticks += 1;
+///     if let Progress::Done(sum) = machine.run_for(3) {
+///         break sum;
+///     }
+/// };
+///
+/// assert_eq!(sum, 10);
+/// assert_eq!(ticks, 4);
+/// ```
+pub struct StackMachine<F: MappableFrame, Expand: Coalgebra<F>, Collapse: Algebra<F>> {
+    ctx: TraversalContext<F, Expand::Seed, Collapse::Carrier>,
+    expand: Expand,
+    collapse: Collapse,
+}
+
+/// A [`StackMachine`] that collapses some [`Collapsible`] value `X` via the algebra `Collapse`
+pub type CollapseMachine<X, Collapse> = StackMachine<
+    <X as Collapsible>::FrameToken,
+    FnCoalgebra<
+        <X as Collapsible>::FrameToken,
+        X,
+        fn(X) -> <<X as Collapsible>::FrameToken as MappableFrame>::Frame<X>,
+    >,
+    Collapse,
+>;
+
+/// A [`StackMachine`] that expands some input into an [`Expandable`] value `X` via the coalgebra `Expand`
+pub type ExpandMachine<X, Expand> = StackMachine<
+    <X as Expandable>::FrameToken,
+    Expand,
+    FnAlgebra<
+        <X as Expandable>::FrameToken,
+        X,
+        fn(<<X as Expandable>::FrameToken as MappableFrame>::Frame<X>) -> X,
+    >,
+>;
+
+impl<F, Expand, Collapse> StackMachine<F, Expand, Collapse>
+where
+    F: MappableFrame,
+    Expand: Coalgebra<F>,
+    Collapse: Algebra<F>,
+{
+    /// Create a new stack machine that will expand `input` via `expand_frame` and collapse the
+    /// resulting frames via `collapse_frame`. No work is done until the machine is stepped.
+    pub fn new<ME, MC>(
+        input: Expand::In,
+        expand_frame: impl IntoCoalgebra<F, Expand::In, ME, Coalgebra = Expand>,
+        collapse_frame: impl IntoAlgebra<F, MC, Algebra = Collapse>,
+    ) -> Self {
+        let mut expand = expand_frame.into_coalgebra();
+        let mut ctx = TraversalContext::new();
+        ctx.start(expand.start(input));
+        Self {
+            ctx,
+            expand,
+            collapse: collapse_frame.into_algebra(),
+        }
+    }
+
+    /// Resume a traversal from some [`Checkpoint`], using the same expand and collapse functions
+    /// as the machine that produced it. No work is done until the machine is stepped.
+    pub fn resume<ME, MC>(
+        checkpoint: Checkpoint<F, Expand::Seed, Collapse::Carrier>,
+        expand_frame: impl IntoCoalgebra<F, Expand::In, ME, Coalgebra = Expand>,
+        collapse_frame: impl IntoAlgebra<F, MC, Algebra = Collapse>,
+    ) -> Self {
+        Self {
+            ctx: TraversalContext { state: checkpoint },
+            expand: expand_frame.into_coalgebra(),
+            collapse: collapse_frame.into_algebra(),
+        }
+    }
+
+    /// The current state of this machine, eg for serializing it between steps
+    pub fn checkpoint(&self) -> &Checkpoint<F, Expand::Seed, Collapse::Carrier> {
+        &self.ctx.state
+    }
+
+    /// Discard the expand and collapse functions, returning the current state of this machine
+    pub fn into_checkpoint(self) -> Checkpoint<F, Expand::Seed, Collapse::Carrier> {
+        self.ctx.state
+    }
+
+    /// Perform a single step of work, either expanding a single seed or collapsing a single frame.
+    ///
+    /// Panics if called after the machine has already returned [`Progress::Done`]
+    pub fn step(&mut self) -> Progress<Collapse::Out> {
+        let item = self
+            .ctx
+            .state
+            .stack
+            .pop()
+            .expect("StackMachine stepped after completion");
+        self.process(item);
+
+        if self.ctx.state.stack.is_empty() {
+            let carrier = self.ctx.finish();
+            Progress::Done(self.collapse.finish(carrier))
+        } else {
+            Progress::Pending
+        }
+    }
+
+    /// Perform at most `budget` steps of work, returning early if the traversal completes.
+    ///
+    /// Panics if called after the machine has already returned [`Progress::Done`]
+    pub fn run_for(&mut self, budget: usize) -> Progress<Collapse::Out> {
+        for _ in 0..budget {
+            if let Progress::Done(out) = self.step() {
+                return Progress::Done(out);
+            }
+        }
+        Progress::Pending
+    }
+
+    /// Run the machine to completion, returning the output value.
+    ///
+    /// Panics if called after the machine has already returned [`Progress::Done`]
+    pub fn run(mut self) -> Collapse::Out {
+        assert!(
+            !self.ctx.state.is_complete(),
+            "StackMachine run after completion"
+        );
+        while let Some(item) = self.ctx.state.stack.pop() {
+            self.process(item);
+        }
+        let carrier = self.ctx.finish();
+        self.collapse.finish(carrier)
+    }
+
+    #[inline(always)]
+    fn process(&mut self, item: State<Expand::Seed, F::Frame<()>>) {
+        let expand = &mut self.expand;
+        let collapse = &mut self.collapse;
+        let res = self.ctx.process::<Infallible>(
+            item,
+            &mut |seed| Ok(expand.expand_frame(seed)),
+            &mut |node| Ok(collapse.collapse_frame(node)),
+        );
+        match res {
+            Ok(()) => {}
+            Err(e) => match e {},
+        }
+    }
+}
+
+/// Resumable traversals that collapse some [`Collapsible`] type, see [`StackMachine`]
+pub trait CollapsibleStepwiseExt: Collapsible {
+    /// Given an instance of this type, build a [`StackMachine`] that collapses it into a single
+    /// value of type `Out` using some function from `Frame<Out> -> Out`, or more generally some [`Algebra`].
+    /// Unlike `collapse_frames`, no work is done up front: the caller drives the traversal via `step` or `run_for`
+    fn collapse_frames_stepwise<M, A: IntoAlgebra<Self::FrameToken, M>>(
+        self,
+        collapse_frame: A,
+    ) -> CollapseMachine<Self, A::Algebra>;
+}
+
+impl<X: Collapsible> CollapsibleStepwiseExt for X {
+    fn collapse_frames_stepwise<M, A: IntoAlgebra<Self::FrameToken, M>>(
+        self,
+        collapse_frame: A,
+    ) -> CollapseMachine<Self, A::Algebra> {
+        let into_frame: fn(Self) -> _ = Self::into_frame;
+        StackMachine::new(self, into_frame, collapse_frame)
+    }
+}
+
+/// Resumable traversals that expand some [`Expandable`] type, see [`StackMachine`]
+pub trait ExpandableStepwiseExt: Expandable {
+    /// Given a value of type `In`, build a [`StackMachine`] that expands it to generate a value of type
+    /// `Self` using a function from `In -> Frame<In>`, or more generally some [`Coalgebra`]. Unlike
+    /// `expand_frames`, no work is done up front: the caller drives the traversal via `step` or `run_for`
+    fn expand_frames_stepwise<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
+        input: In,
+        expand_frame: C,
+    ) -> ExpandMachine<Self, C::Coalgebra>;
+}
+
+impl<X: Expandable> ExpandableStepwiseExt for X {
+    fn expand_frames_stepwise<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
+        input: In,
+        expand_frame: C,
+    ) -> ExpandMachine<Self, C::Coalgebra> {
+        let from_frame: fn(_) -> Self = Self::from_frame;
+        StackMachine::new(input, expand_frame, from_frame)
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::collections::BTreeMap;
+
+use crate::frame::{FoldableFrame, MappableFrame};
+use crate::recursive::collapse::{Collapsible, CollapsibleExt};
+
+/// Summary statistics describing the shape of some recursive structure,
+/// as computed by [`CollapsibleMetricsExt::tree_metrics`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TreeMetrics {
+    /// The total number of nodes (frames) in the structure
+    pub node_count: usize,
+    /// The number of nodes with no children
+    pub leaf_count: usize,
+    /// The number of edges on the longest path from the root to a leaf. A lone leaf has height 0.
+    pub height: usize,
+    /// For each number of children, the number of nodes with exactly that many children
+    pub branching_histogram: BTreeMap<usize, usize>,
+}
+
+/// Generic metrics for any [`Collapsible`] type whose frames implement [`FoldableFrame`].
+///
+/// Each of these is a single stack safe traversal via [`CollapsibleExt::collapse_frames`].
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum TreeFrame<A> {
+/// #     Leaf(usize),
+/// #     Node(A, A),
+/// # }
+/// # impl MappableFrame for TreeFrame<PartiallyApplied> {
+/// #     type Frame<X> = TreeFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             TreeFrame::Leaf(x) => TreeFrame::Leaf(x),
+/// #             TreeFrame::Node(a, b) => TreeFrame::Node(f(a), f(b)),
+/// #         }
+/// #     }
+/// # }
+/// # impl FoldableFrame for TreeFrame<PartiallyApplied> {
+/// #     fn fold_frame<A, Acc>(input: &Self::Frame<A>, init: Acc, mut f: impl FnMut(Acc, &A) -> Acc) -> Acc {
+/// #         match input {
+/// #             TreeFrame::Leaf(_) => init,
+/// #             TreeFrame::Node(a, b) => {
+/// #                 let acc = f(init, a);
+/// #                 f(acc, b)
+/// #             }
+/// #         }
+/// #     }
+/// # }
+/// // a perfect binary tree of the given depth, generated on the fly
+/// struct Perfect(usize);
+///
+/// impl Collapsible for Perfect {
+///     type FrameToken = TreeFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+///         match self.0 {
+///             0 => TreeFrame::Leaf(0),
+///             n => TreeFrame::Node(Perfect(n - 1), Perfect(n - 1)),
+///         }
+///     }
+/// }
+///
+/// let metrics = Perfect(3).tree_metrics();
+///
+/// assert_eq!(metrics.node_count, 15);
+/// assert_eq!(metrics.leaf_count, 8);
+/// assert_eq!(metrics.height, 3);
+/// assert_eq!(metrics.branching_histogram.get(&2), Some(&7));
+/// assert_eq!(metrics.branching_histogram.get(&0), Some(&8));
+/// ```
+pub trait CollapsibleMetricsExt: Collapsible
+where
+    Self::FrameToken: FoldableFrame,
+{
+    /// The total number of nodes (frames) in this structure
+    fn node_count(self) -> usize;
+
+    /// The number of nodes in this structure with no children
+    fn leaf_count(self) -> usize;
+
+    /// The number of edges on the longest path from the root of this structure to a leaf
+    fn height(self) -> usize;
+
+    /// For each number of children, the number of nodes in this structure with exactly that many children
+    fn branching_histogram(self) -> BTreeMap<usize, usize>;
+
+    /// All of the above metrics, computed in a single traversal
+    fn tree_metrics(self) -> TreeMetrics;
+}
+
+impl<X> CollapsibleMetricsExt for X
+where
+    X: Collapsible,
+    X::FrameToken: FoldableFrame,
+{
+    fn node_count(self) -> usize {
+        self.collapse_frames(|frame| 1 + X::FrameToken::fold_frame(&frame, 0, |acc, n| acc + n))
+    }
+
+    fn leaf_count(self) -> usize {
+        self.collapse_frames(|frame| {
+            if X::FrameToken::child_count(&frame) == 0 {
+                1
+            } else {
+                X::FrameToken::fold_frame(&frame, 0, |acc, n| acc + n)
+            }
+        })
+    }
+
+    fn height(self) -> usize {
+        self.collapse_frames(|frame| height_layer::<X::FrameToken>(&frame))
+    }
+
+    fn branching_histogram(self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        self.collapse_frames(|frame: <X::FrameToken as MappableFrame>::Frame<()>| {
+            *histogram
+                .entry(X::FrameToken::child_count(&frame))
+                .or_insert(0) += 1;
+        });
+        histogram
+    }
+
+    fn tree_metrics(self) -> TreeMetrics {
+        let mut metrics = TreeMetrics::default();
+        metrics.height = self.collapse_frames(|frame| {
+            let children = X::FrameToken::child_count(&frame);
+            metrics.node_count += 1;
+            if children == 0 {
+                metrics.leaf_count += 1;
+            }
+            *metrics.branching_histogram.entry(children).or_insert(0) += 1;
+            height_layer::<X::FrameToken>(&frame)
+        });
+        metrics
+    }
+}
+
+fn height_layer<F: FoldableFrame>(frame: &F::Frame<usize>) -> usize {
+    F::fold_frame(frame, None, |acc: Option<usize>, h| {
+        Some(acc.map_or(*h, |acc| acc.max(*h)))
+    })
+    .map_or(0, |h| h + 1)
+}
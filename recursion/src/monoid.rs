@@ -0,0 +1,239 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::iter;
+
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::Collapsible;
+
+/// A type with an associative way to combine two values, and an empty value that leaves any other
+/// value unchanged when combined with it, as used by [`CollapsibleMonoidExt::fold_map_frames`].
+///
+/// # Implementing this trait
+///
+/// `combine` must be associative: `a.combine(b).combine(c)` must equal `a.combine(b.combine(c))`.
+/// It need not be commutative, as values are always combined in a well-defined order.
+///
+/// ```rust
+/// # use recursion::Monoid;
+/// #[derive(Debug, PartialEq)]
+/// struct Longest(String);
+///
+/// impl Monoid for Longest {
+///     fn empty() -> Self {
+///         Longest(String::new())
+///     }
+///
+///     // keeps the first of two equally long strings, so this is associative
+///     fn combine(self, other: Self) -> Self {
+///         if other.0.len() > self.0.len() { other } else { self }
+///     }
+/// }
+///
+/// let words = ["a", "bcd", "efg", "hi"].map(|w| Longest(w.to_string()));
+/// assert_eq!(words.into_iter().fold(Longest::empty(), Monoid::combine), Longest("bcd".to_string()));
+/// ```
+pub trait Monoid {
+    /// The identity element
+    fn empty() -> Self;
+
+    /// Combine two values, with `self` coming before `other`
+    fn combine(self, other: Self) -> Self;
+}
+
+/// A monoid that adds values together, with zero as its empty value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sum<T>(pub T);
+
+impl<T: iter::Sum> Monoid for Sum<T> {
+    fn empty() -> Self {
+        Sum(iter::empty::<T>().sum())
+    }
+
+    fn combine(self, other: Self) -> Self {
+        Sum([self.0, other.0].into_iter().sum())
+    }
+}
+
+/// A monoid that multiplies values together, with one as its empty value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Product<T>(pub T);
+
+impl<T: iter::Product> Monoid for Product<T> {
+    fn empty() -> Self {
+        Product(iter::empty::<T>().product())
+    }
+
+    fn combine(self, other: Self) -> Self {
+        Product([self.0, other.0].into_iter().product())
+    }
+}
+
+/// A monoid that keeps the smallest value, if any. Keeps the first of two equal values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Min<T>(pub Option<T>);
+
+impl<T: Ord> Monoid for Min<T> {
+    fn empty() -> Self {
+        Min(None)
+    }
+
+    fn combine(self, other: Self) -> Self {
+        match (self.0, other.0) {
+            (Some(a), Some(b)) if b < a => Min(Some(b)),
+            (Some(a), _) => Min(Some(a)),
+            (None, b) => Min(b),
+        }
+    }
+}
+
+/// A monoid that keeps the largest value, if any. Keeps the first of two equal values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Max<T>(pub Option<T>);
+
+impl<T: Ord> Monoid for Max<T> {
+    fn empty() -> Self {
+        Max(None)
+    }
+
+    fn combine(self, other: Self) -> Self {
+        match (self.0, other.0) {
+            (Some(a), Some(b)) if b > a => Max(Some(b)),
+            (Some(a), _) => Max(Some(a)),
+            (None, b) => Max(b),
+        }
+    }
+}
+
+/// Concatenation
+impl<T> Monoid for Vec<T> {
+    fn empty() -> Self {
+        Vec::new()
+    }
+
+    fn combine(mut self, mut other: Self) -> Self {
+        self.append(&mut other);
+        self
+    }
+}
+
+/// Union
+impl<T: Ord> Monoid for BTreeSet<T> {
+    fn empty() -> Self {
+        BTreeSet::new()
+    }
+
+    fn combine(mut self, mut other: Self) -> Self {
+        // merge the smaller set into the larger one
+        if self.len() < other.len() {
+            core::mem::swap(&mut self, &mut other);
+        }
+        self.extend(other);
+        self
+    }
+}
+
+/// Union
+#[cfg(feature = "std")]
+impl<T: Eq + core::hash::Hash, S: core::hash::BuildHasher + Default> Monoid
+    for std::collections::HashSet<T, S>
+{
+    fn empty() -> Self {
+        Self::default()
+    }
+
+    fn combine(mut self, mut other: Self) -> Self {
+        // merge the smaller set into the larger one
+        if self.len() < other.len() {
+            core::mem::swap(&mut self, &mut other);
+        }
+        self.extend(other);
+        self
+    }
+}
+
+/// Combines both elements separately, such that several monoids can be computed in a single fold
+impl<A: Monoid, B: Monoid> Monoid for (A, B) {
+    fn empty() -> Self {
+        (A::empty(), B::empty())
+    }
+
+    fn combine(self, other: Self) -> Self {
+        (self.0.combine(other.0), self.1.combine(other.1))
+    }
+}
+
+/// Folds over [`Collapsible`] types that visit each node in turn, combining a value for each node via some [`Monoid`]
+pub trait CollapsibleMonoidExt: Collapsible {
+    /// Map each node of this structure to some [`Monoid`] via `f`, which is passed each frame with its
+    /// child positions erased, and combine the results. Nodes are combined in pre-order: each node's value
+    /// comes before those of its children, which are combined in the order visited by `map_frame`.
+    ///
+    /// As monoids are associative, only this order is observable, not how the values are grouped together.
+    /// This visits each node in turn, with no need to keep partial results for incomplete subtrees.
+    ///
+    /// ```rust
+    /// # use recursion::*;
+    /// # enum ExprFrame<A> {
+    /// #     Add(A, A),
+    /// #     LiteralInt(i64),
+    /// # }
+    /// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+    /// #     type Frame<X> = ExprFrame<X>;
+    /// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+    /// #         match input {
+    /// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+    /// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+    /// #         }
+    /// #     }
+    /// # }
+    /// # enum Expr {
+    /// #     Add(Box<Expr>, Box<Expr>),
+    /// #     LiteralInt(i64),
+    /// # }
+    /// # impl<'a> Collapsible for &'a Expr {
+    /// #     type FrameToken = ExprFrame<PartiallyApplied>;
+    /// #     fn into_frame(self) -> ExprFrame<&'a Expr> {
+    /// #         match self {
+    /// #             Expr::Add(a, b) => ExprFrame::Add(a, b),
+    /// #             Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+    /// #         }
+    /// #     }
+    /// # }
+    /// // (1 + 2) + 3
+    /// let lit = |x| Box::new(Expr::LiteralInt(x));
+    /// let expr = Expr::Add(Box::new(Expr::Add(lit(1), lit(2))), lit(3));
+    ///
+    /// let (literals, Max(largest)) = (&expr).fold_map_frames(|frame| match frame {
+    ///     ExprFrame::LiteralInt(x) => (vec![x], Max(Some(x))),
+    ///     ExprFrame::Add((), ()) => Monoid::empty(),
+    /// });
+    ///
+    /// assert_eq!(literals, vec![1, 2, 3]);
+    /// assert_eq!(largest, Some(3));
+    /// ```
+    fn fold_map_frames<M: Monoid>(
+        self,
+        f: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<()>) -> M,
+    ) -> M;
+}
+
+impl<X: Collapsible> CollapsibleMonoidExt for X {
+    fn fold_map_frames<M: Monoid>(
+        self,
+        mut f: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<()>) -> M,
+    ) -> M {
+        let mut acc = M::empty();
+        let mut stack = Vec::new();
+        let mut children = Vec::new();
+        stack.push(self);
+
+        while let Some(node) = stack.pop() {
+            let frame =
+                Self::FrameToken::map_frame(node.into_frame(), |child| children.push(child));
+            acc = acc.combine(f(frame));
+            stack.extend(children.drain(..).rev());
+        }
+
+        acc
+    }
+}
@@ -0,0 +1,364 @@
+use core::cell::RefCell;
+use core::marker::PhantomData;
+
+use crate::frame::{expand_and_collapse, MappableFrame};
+
+/// A value of one of the two sorts of a mutually recursive structure, used as the input and output
+/// of traversals over such structures (see [`MutualCollapsibleExt`] and [`MutualExpandableExt`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sort<A, B> {
+    /// A value of the first sort, e.g. an expression
+    First(A),
+    /// A value of the second sort, e.g. a statement
+    Second(B),
+}
+
+impl<A, B> Sort<A, B> {
+    /// The value of the first sort, if any
+    pub fn first(self) -> Option<A> {
+        match self {
+            Sort::First(a) => Some(a),
+            Sort::Second(_) => None,
+        }
+    }
+
+    /// The value of the second sort, if any
+    pub fn second(self) -> Option<B> {
+        match self {
+            Sort::First(_) => None,
+            Sort::Second(b) => Some(b),
+        }
+    }
+}
+
+/// The multi-sorted counterpart of [`MappableFrame`], for two mutually recursive types such as the
+/// expressions and statements of some language, where expressions can contain statements and vice versa.
+///
+/// Each sort has its own frame type, and each frame type has two type parameters: one for recursive
+/// positions of the first sort and one for recursive positions of the second sort.
+///
+/// Exactly two sorts are supported. Structures with three or more mutually recursive types can be
+/// traversed by making the second sort an enum over all but the first, with `Second` frames being an
+/// enum over those types' frames.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// enum ExprFrame<E, S> {
+///     Lit(i64),
+///     Add(E, E),
+///     Block(Vec<S>, E),
+/// }
+///
+/// enum StmtFrame<E> {
+///     Print(E),
+/// }
+///
+/// enum Lang {}
+///
+/// impl MutualFrame for Lang {
+///     type First<E, S> = ExprFrame<E, S>;
+///     type Second<E, S> = StmtFrame<E>;
+///
+///     fn map_first<A, B, C, D>(
+///         input: ExprFrame<A, B>,
+///         mut f: impl FnMut(A) -> C,
+///         g: impl FnMut(B) -> D,
+///     ) -> ExprFrame<C, D> {
+///         match input {
+///             ExprFrame::Lit(x) => ExprFrame::Lit(x),
+///             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+///             ExprFrame::Block(stmts, e) => {
+///                 let stmts = stmts.into_iter().map(g).collect();
+///                 ExprFrame::Block(stmts, f(e))
+///             }
+///         }
+///     }
+///
+///     fn map_second<A, B, C, D>(
+///         input: StmtFrame<A>,
+///         mut f: impl FnMut(A) -> C,
+///         _g: impl FnMut(B) -> D,
+///     ) -> StmtFrame<C> {
+///         match input {
+///             StmtFrame::Print(e) => StmtFrame::Print(f(e)),
+///         }
+///     }
+/// }
+///
+/// enum Expr {
+///     Lit(i64),
+///     Add(Box<Expr>, Box<Expr>),
+///     Block(Vec<Stmt>, Box<Expr>),
+/// }
+///
+/// enum Stmt {
+///     Print(Expr),
+/// }
+///
+/// impl<'a> MutualCollapsible for &'a Expr {
+///     type FrameToken = Lang;
+///     type Second = &'a Stmt;
+///
+///     fn into_first_frame(self) -> ExprFrame<&'a Expr, &'a Stmt> {
+///         match self {
+///             Expr::Lit(x) => ExprFrame::Lit(*x),
+///             Expr::Add(a, b) => ExprFrame::Add(a, b),
+///             Expr::Block(stmts, e) => ExprFrame::Block(stmts.iter().collect(), e),
+///         }
+///     }
+///
+///     fn into_second_frame(stmt: &'a Stmt) -> StmtFrame<&'a Expr> {
+///         match stmt {
+///             Stmt::Print(e) => StmtFrame::Print(e),
+///         }
+///     }
+/// }
+///
+/// // { print 1 + 2; 3 }
+/// let expr = Expr::Block(
+///     vec![Stmt::Print(Expr::Add(Box::new(Expr::Lit(1)), Box::new(Expr::Lit(2))))],
+///     Box::new(Expr::Lit(3)),
+/// );
+///
+/// fn render_expr(frame: ExprFrame<String, String>) -> String {
+///     match frame {
+///         ExprFrame::Lit(x) => x.to_string(),
+///         ExprFrame::Add(a, b) => format!("{} + {}", a, b),
+///         ExprFrame::Block(stmts, e) => format!("{{ {}{} }}", stmts.concat(), e),
+///     }
+/// }
+///
+/// fn render_stmt(frame: StmtFrame<String>) -> String {
+///     match frame {
+///         StmtFrame::Print(e) => format!("print {}; ", e),
+///     }
+/// }
+///
+/// let rendered = <&Expr>::collapse_mutual(Sort::First(&expr), render_expr, render_stmt);
+/// assert_eq!(rendered, Sort::First("{ print 1 + 2; 3 }".to_string()));
+///
+/// impl MutualExpandable for Expr {
+///     type FrameToken = Lang;
+///     type Second = Stmt;
+///
+///     fn from_first_frame(frame: ExprFrame<Expr, Stmt>) -> Expr {
+///         match frame {
+///             ExprFrame::Lit(x) => Expr::Lit(x),
+///             ExprFrame::Add(a, b) => Expr::Add(Box::new(a), Box::new(b)),
+///             ExprFrame::Block(stmts, e) => Expr::Block(stmts, Box::new(e)),
+///         }
+///     }
+///
+///     fn from_second_frame(frame: StmtFrame<Expr>) -> Stmt {
+///         match frame {
+///             StmtFrame::Print(e) => Stmt::Print(e),
+///         }
+///     }
+/// }
+///
+/// // blocks nested `depth` deep, each printing the next
+/// let nested = Expr::expand_mutual(
+///     Sort::First(2),
+///     |depth: u32| match depth {
+///         0 => ExprFrame::Lit(0),
+///         _ => ExprFrame::Block(vec![depth - 1], 0),
+///     },
+///     |depth: u32| StmtFrame::Print(depth),
+/// );
+///
+/// let nested = nested.first().unwrap();
+/// let rendered = <&Expr>::collapse_mutual(Sort::First(&nested), render_expr, render_stmt);
+/// assert_eq!(rendered, Sort::First("{ print { print 0; 0 }; 0 }".to_string()));
+/// ```
+pub trait MutualFrame {
+    /// Frames of the first sort, with recursive positions of type `A` (first sort) and `B` (second sort)
+    type First<A, B>;
+
+    /// Frames of the second sort, with recursive positions of type `A` (first sort) and `B` (second sort)
+    type Second<A, B>;
+
+    /// Apply `f` to each recursive position of the first sort and `g` to each recursive position of the
+    /// second sort inside a frame of the first sort
+    fn map_first<A, B, C, D>(
+        input: Self::First<A, B>,
+        f: impl FnMut(A) -> C,
+        g: impl FnMut(B) -> D,
+    ) -> Self::First<C, D>;
+
+    /// Apply `f` to each recursive position of the first sort and `g` to each recursive position of the
+    /// second sort inside a frame of the second sort
+    fn map_second<A, B, C, D>(
+        input: Self::Second<A, B>,
+        f: impl FnMut(A) -> C,
+        g: impl FnMut(B) -> D,
+    ) -> Self::Second<C, D>;
+}
+
+/// The multi-sorted counterpart of [`crate::Collapsible`], implemented for the first of two mutually
+/// recursive types, with `Second` naming the other.
+pub trait MutualCollapsible: Sized {
+    /// The frames of both sorts of this mutually recursive structure
+    type FrameToken: MutualFrame;
+
+    /// The second sort of this mutually recursive structure
+    type Second;
+
+    /// Given a value of the first sort, return a single layer of structure
+    fn into_first_frame(self) -> <Self::FrameToken as MutualFrame>::First<Self, Self::Second>;
+
+    /// Given a value of the second sort, return a single layer of structure
+    fn into_second_frame(
+        second: Self::Second,
+    ) -> <Self::FrameToken as MutualFrame>::Second<Self, Self::Second>;
+}
+
+/// The multi-sorted counterpart of [`crate::Expandable`], implemented for the first of two mutually
+/// recursive types, with `Second` naming the other.
+pub trait MutualExpandable: Sized {
+    /// The frames of both sorts of this mutually recursive structure
+    type FrameToken: MutualFrame;
+
+    /// The second sort of this mutually recursive structure
+    type Second;
+
+    /// Given a single layer of structure of the first sort, build a value of the first sort
+    fn from_first_frame(
+        frame: <Self::FrameToken as MutualFrame>::First<Self, Self::Second>,
+    ) -> Self;
+
+    /// Given a single layer of structure of the second sort, build a value of the second sort
+    fn from_second_frame(
+        frame: <Self::FrameToken as MutualFrame>::Second<Self, Self::Second>,
+    ) -> Self::Second;
+}
+
+/// Stack-safe collapsing for [`MutualCollapsible`] types
+pub trait MutualCollapsibleExt: MutualCollapsible {
+    /// Given a value of either sort, collapse it into a single value of type `First` or `Second`
+    /// (depending on its sort) using a function per sort, starting from the leaves. This uses the
+    /// same stack-safe machinery as [`crate::CollapsibleExt::collapse_frames`], with a single stack
+    /// shared between both sorts.
+    #[allow(clippy::type_complexity)]
+    fn collapse_mutual<First, Second>(
+        input: Sort<Self, Self::Second>,
+        collapse_first: impl FnMut(<Self::FrameToken as MutualFrame>::First<First, Second>) -> First,
+        collapse_second: impl FnMut(<Self::FrameToken as MutualFrame>::Second<First, Second>) -> Second,
+    ) -> Sort<First, Second>;
+}
+
+impl<X: MutualCollapsible> MutualCollapsibleExt for X {
+    fn collapse_mutual<First, Second>(
+        input: Sort<Self, Self::Second>,
+        collapse_first: impl FnMut(<Self::FrameToken as MutualFrame>::First<First, Second>) -> First,
+        collapse_second: impl FnMut(<Self::FrameToken as MutualFrame>::Second<First, Second>) -> Second,
+    ) -> Sort<First, Second> {
+        traverse::<Self::FrameToken, _, _, _, _>(
+            input,
+            X::into_first_frame,
+            X::into_second_frame,
+            collapse_first,
+            collapse_second,
+        )
+    }
+}
+
+/// Stack-safe expansion for [`MutualExpandable`] types
+pub trait MutualExpandableExt: MutualExpandable {
+    /// Given a seed of either sort, expand it into a value of the corresponding sort using a function
+    /// per sort, starting from the root. This uses the same stack-safe machinery as
+    /// [`crate::ExpandableExt::expand_frames`], with a single stack shared between both sorts.
+    #[allow(clippy::type_complexity)]
+    fn expand_mutual<First, Second>(
+        seed: Sort<First, Second>,
+        expand_first: impl FnMut(First) -> <Self::FrameToken as MutualFrame>::First<First, Second>,
+        expand_second: impl FnMut(Second) -> <Self::FrameToken as MutualFrame>::Second<First, Second>,
+    ) -> Sort<Self, Self::Second>;
+}
+
+impl<X: MutualExpandable> MutualExpandableExt for X {
+    fn expand_mutual<First, Second>(
+        seed: Sort<First, Second>,
+        expand_first: impl FnMut(First) -> <Self::FrameToken as MutualFrame>::First<First, Second>,
+        expand_second: impl FnMut(Second) -> <Self::FrameToken as MutualFrame>::Second<First, Second>,
+    ) -> Sort<Self, Self::Second> {
+        traverse::<Self::FrameToken, _, _, _, _>(
+            seed,
+            expand_first,
+            expand_second,
+            X::from_first_frame,
+            X::from_second_frame,
+        )
+    }
+}
+
+/// Expand and collapse a mutually recursive structure by running the single-sorted machinery over
+/// [`Sorted`] frames, with each seed and output tagged with its sort
+fn traverse<F: MutualFrame, SeedA, SeedB, OutA, OutB>(
+    seed: Sort<SeedA, SeedB>,
+    mut expand_first: impl FnMut(SeedA) -> F::First<SeedA, SeedB>,
+    mut expand_second: impl FnMut(SeedB) -> F::Second<SeedA, SeedB>,
+    mut collapse_first: impl FnMut(F::First<OutA, OutB>) -> OutA,
+    mut collapse_second: impl FnMut(F::Second<OutA, OutB>) -> OutB,
+) -> Sort<OutA, OutB> {
+    expand_and_collapse::<Sorted<F>, Sort<SeedA, SeedB>, Sort<OutA, OutB>>(
+        seed,
+        |seed| match seed {
+            Sort::First(seed) => {
+                Sort::First(F::map_first(expand_first(seed), Sort::First, Sort::Second))
+            }
+            Sort::Second(seed) => Sort::Second(F::map_second(
+                expand_second(seed),
+                Sort::First,
+                Sort::Second,
+            )),
+        },
+        |frame| match frame {
+            Sort::First(frame) => Sort::First(collapse_first(F::map_first(
+                frame,
+                expect_first,
+                expect_second,
+            ))),
+            Sort::Second(frame) => Sort::Second(collapse_second(F::map_second(
+                frame,
+                expect_first,
+                expect_second,
+            ))),
+        },
+    )
+}
+
+fn expect_first<A, B>(out: Sort<A, B>) -> A {
+    out.first()
+        .expect("MutualFrame mapped a position of the second sort as the first")
+}
+
+fn expect_second<A, B>(out: Sort<A, B>) -> B {
+    out.second()
+        .expect("MutualFrame mapped a position of the first sort as the second")
+}
+
+/// A single-sorted view of some [`MutualFrame`], where every recursive position holds a value tagged with its sort
+struct Sorted<F>(PhantomData<F>);
+
+impl<F: MutualFrame> MappableFrame for Sorted<F> {
+    type Frame<X> = Sort<F::First<X, X>, F::Second<X, X>>;
+
+    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        // both sorts' positions are mapped with the same function, which is only ever called by one of them at a time
+        let f = RefCell::new(f);
+        match input {
+            Sort::First(frame) => Sort::First(F::map_first(
+                frame,
+                |a| (f.borrow_mut())(a),
+                |a| (f.borrow_mut())(a),
+            )),
+            Sort::Second(frame) => Sort::Second(F::map_second(
+                frame,
+                |a| (f.borrow_mut())(a),
+                |a| (f.borrow_mut())(a),
+            )),
+        }
+    }
+}
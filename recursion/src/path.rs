@@ -0,0 +1,345 @@
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::frame::{expand_and_collapse, try_expand_and_collapse, MappableFrame};
+use crate::recursive::collapse::Collapsible;
+
+/// The label of a single child position within a frame, as assigned by [`LabelledFrame`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Label {
+    /// A named child position, such as `lhs` or `cond`
+    Name(&'static str),
+    /// A child position identified by its index, such as the elements of a `Vec`
+    Index(usize),
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Label::Name(name) => f.write_str(name),
+            Label::Index(idx) => write!(f, "{}", idx),
+        }
+    }
+}
+
+/// A [`MappableFrame`] that gives a label to each of its child positions, such as `lhs`/`rhs` or
+/// `cond`/`then`/`else`, as used by [`CollapsiblePathExt`] to report the [`Path`] to each node.
+///
+/// Frames that don't implement this trait can still be traversed with index-based paths by wrapping
+/// them in [`Positional`].
+///
+/// # Implementing this trait
+///
+/// `label_children` must visit child positions in the same order as `map_frame`, without otherwise
+/// changing the frame.
+///
+/// ```rust
+/// # use recursion::{Label, LabelledFrame, MappableFrame, PartiallyApplied};
+/// enum IfFrame<A> {
+///     If { cond: A, then: A, otherwise: A },
+///     Bool(bool),
+/// }
+///
+/// # impl MappableFrame for IfFrame<PartiallyApplied> {
+/// #     type Frame<X> = IfFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             IfFrame::If { cond, then, otherwise } => IfFrame::If { cond: f(cond), then: f(then), otherwise: f(otherwise) },
+/// #             IfFrame::Bool(b) => IfFrame::Bool(b),
+/// #         }
+/// #     }
+/// # }
+/// impl LabelledFrame for IfFrame<PartiallyApplied> {
+///     fn label_children<A>(input: IfFrame<A>) -> IfFrame<(Label, A)> {
+///         match input {
+///             IfFrame::If { cond, then, otherwise } => IfFrame::If {
+///                 cond: (Label::Name("cond"), cond),
+///                 then: (Label::Name("then"), then),
+///                 otherwise: (Label::Name("else"), otherwise),
+///             },
+///             IfFrame::Bool(b) => IfFrame::Bool(b),
+///         }
+///     }
+/// }
+/// ```
+pub trait LabelledFrame: MappableFrame {
+    /// Pair each child position inside a frame with its label
+    fn label_children<A>(input: Self::Frame<A>) -> Self::Frame<(Label, A)>;
+}
+
+/// The path from the root of some structure to one of its nodes, as a sequence of child [`Label`]s.
+/// Displayed as `root.lhs.rhs`, or as `root` for the root itself.
+///
+/// Paths share their prefixes, so extending a path with [`Path::child`] takes constant time and space.
+#[derive(Clone, Default)]
+pub struct Path(Option<Rc<Segment>>);
+
+struct Segment {
+    parent: Path,
+    label: Label,
+    depth: usize,
+}
+
+impl Path {
+    /// The path to the root
+    pub fn root() -> Self {
+        Self(None)
+    }
+
+    /// The path to the child of this node with the given label
+    pub fn child(&self, label: Label) -> Self {
+        Self(Some(Rc::new(Segment {
+            parent: self.clone(),
+            label,
+            depth: self.depth() + 1,
+        })))
+    }
+
+    /// The number of labels in this path, which is zero for the root
+    pub fn depth(&self) -> usize {
+        self.0.as_ref().map_or(0, |segment| segment.depth)
+    }
+
+    /// The label of the last child position in this path, if this isn't the root
+    pub fn last(&self) -> Option<Label> {
+        self.0.as_ref().map(|segment| segment.label)
+    }
+
+    /// The parent of this path, if this isn't the root
+    pub fn parent(&self) -> Option<&Path> {
+        self.0.as_ref().map(|segment| &segment.parent)
+    }
+
+    /// The labels in this path, starting from the root
+    pub fn labels(&self) -> Vec<Label> {
+        let mut labels = Vec::with_capacity(self.depth());
+        let mut path = self;
+        while let Some(segment) = &path.0 {
+            labels.push(segment.label);
+            path = &segment.parent;
+        }
+        labels.reverse();
+        labels
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("root")?;
+        for label in self.labels() {
+            write!(f, ".{}", label)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Path({})", self)
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        self.depth() == other.depth() && self.labels() == other.labels()
+    }
+}
+
+impl Eq for Path {}
+
+impl Drop for Path {
+    fn drop(&mut self) {
+        // drop long chains of uniquely owned segments one at a time, rather than recursively
+        let mut next = self.0.take();
+        while let Some(segment) = next {
+            next = match Rc::try_unwrap(segment) {
+                Ok(mut segment) => segment.parent.0.take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+/// Wraps a [`Collapsible`] value such that its child positions are labelled by index, for use with
+/// [`CollapsiblePathExt`] when its frames don't implement [`LabelledFrame`].
+///
+/// Also used as the frame token for such values: `Positional<F>` has the same frames as `F`.
+#[derive(Debug)]
+pub struct Positional<C>(pub C);
+
+impl<F: MappableFrame> MappableFrame for Positional<F> {
+    type Frame<X> = F::Frame<X>;
+
+    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        F::map_frame(input, f)
+    }
+}
+
+impl<F: MappableFrame> LabelledFrame for Positional<F> {
+    fn label_children<A>(input: Self::Frame<A>) -> Self::Frame<(Label, A)> {
+        let idx = Cell::new(0);
+        F::map_frame(input, |a| {
+            let label = Label::Index(idx.get());
+            idx.set(idx.get() + 1);
+            (label, a)
+        })
+    }
+}
+
+impl<C: Collapsible> Collapsible for Positional<C> {
+    type FrameToken = Positional<C::FrameToken>;
+
+    fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+        C::FrameToken::map_frame(self.0.into_frame(), Positional)
+    }
+}
+
+/// Traversals that report the [`Path`] to each node, for [`Collapsible`] types whose frames have
+/// labelled child positions (see [`LabelledFrame`] and [`Positional`]).
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # #[derive(Debug)]
+/// # enum ExprFrame<A> {
+/// #     Div(A, A),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Div(a, b) => ExprFrame::Div(f(a), f(b)),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// impl LabelledFrame for ExprFrame<PartiallyApplied> {
+///     fn label_children<A>(input: ExprFrame<A>) -> ExprFrame<(Label, A)> {
+///         match input {
+///             ExprFrame::Div(a, b) => ExprFrame::Div((Label::Name("lhs"), a), (Label::Name("rhs"), b)),
+///             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+///         }
+///     }
+/// }
+///
+/// enum Expr {
+///     Div(Box<Expr>, Box<Expr>),
+///     LiteralInt(i64),
+/// }
+///
+/// impl<'a> Collapsible for &'a Expr {
+///     type FrameToken = ExprFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ExprFrame<&'a Expr> {
+///         match self {
+///             Expr::Div(a, b) => ExprFrame::Div(a, b),
+///             Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+///         }
+///     }
+/// }
+///
+/// // 1 / (2 / 0)
+/// let lit = |x| Box::new(Expr::LiteralInt(x));
+/// let expr = Expr::Div(lit(1), Box::new(Expr::Div(lit(2), lit(0))));
+///
+/// let eval = |path: &Path, frame| match frame {
+///     ExprFrame::Div(_, 0) => Err(format!("division by zero at {}", path)),
+///     ExprFrame::Div(a, b) => Ok(a / b),
+///     ExprFrame::LiteralInt(x) => Ok(x),
+/// };
+///
+/// assert_eq!(
+///     (&expr).try_collapse_frames_with_path(eval),
+///     Err("division by zero at root.rhs".to_string())
+/// );
+///
+/// // frames without labels can be traversed by index instead
+/// let mut paths = Vec::new();
+/// Positional(&expr).collapse_frames_with_path(|path, _frame| paths.push(path.to_string()));
+/// paths.sort();
+/// assert_eq!(paths, ["root", "root.0", "root.1", "root.1.0", "root.1.1"]);
+/// ```
+pub trait CollapsiblePathExt: Collapsible
+where
+    Self::FrameToken: LabelledFrame,
+{
+    /// Given an instance of this type, collapse it into a single value of type `Out` using some
+    /// function from `Frame<Out> -> Out`, as with [`crate::CollapsibleExt::collapse_frames`],
+    /// passing the path to each node along with its frame.
+    fn collapse_frames_with_path<Out>(
+        self,
+        collapse_frame: impl FnMut(&Path, <Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
+    ) -> Out;
+
+    /// Given an instance of this type, collapse it into a single value of type `Result<Out, E>` using some
+    /// fallible function from `Frame<Out> -> Result<Out, E>`, as with [`crate::CollapsibleExt::try_collapse_frames`],
+    /// passing the path to each node along with its frame. The path can be used to report where an error occurred.
+    fn try_collapse_frames_with_path<Out, E>(
+        self,
+        collapse_frame: impl FnMut(
+            &Path,
+            <Self::FrameToken as MappableFrame>::Frame<Out>,
+        ) -> Result<Out, E>,
+    ) -> Result<Out, E>;
+}
+
+impl<X> CollapsiblePathExt for X
+where
+    X: Collapsible,
+    X::FrameToken: LabelledFrame,
+{
+    fn collapse_frames_with_path<Out>(
+        self,
+        mut collapse_frame: impl FnMut(&Path, <Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
+    ) -> Out {
+        expand_and_collapse::<WithPath<Self::FrameToken>, (Path, Self), Out>(
+            (Path::root(), self),
+            expand_with_path::<Self>,
+            |(path, frame)| collapse_frame(&path, frame),
+        )
+    }
+
+    fn try_collapse_frames_with_path<Out, E>(
+        self,
+        mut collapse_frame: impl FnMut(
+            &Path,
+            <Self::FrameToken as MappableFrame>::Frame<Out>,
+        ) -> Result<Out, E>,
+    ) -> Result<Out, E> {
+        try_expand_and_collapse::<WithPath<Self::FrameToken>, (Path, Self), Out, E>(
+            (Path::root(), self),
+            |seed| Ok(expand_with_path::<Self>(seed)),
+            |(path, frame)| collapse_frame(&path, frame),
+        )
+    }
+}
+
+fn expand_with_path<C>(
+    (path, node): (Path, C),
+) -> (Path, <C::FrameToken as MappableFrame>::Frame<(Path, C)>)
+where
+    C: Collapsible,
+    C::FrameToken: LabelledFrame,
+{
+    let frame = C::FrameToken::map_frame(
+        C::FrameToken::label_children(node.into_frame()),
+        |(label, child)| (path.child(label), child),
+    );
+    (path, frame)
+}
+
+/// A frame token for frames of `F` paired with the path to the node they were taken from
+struct WithPath<F>(PhantomData<F>);
+
+impl<F: MappableFrame> MappableFrame for WithPath<F> {
+    type Frame<X> = (Path, F::Frame<X>);
+
+    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        (input.0, F::map_frame(input.1, f))
+    }
+}
@@ -0,0 +1,378 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::{Collapsible, CollapsibleExt};
+
+/// An opaque handle to an already-laid-out child, used to place it within its parent's [`Layout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Child(usize);
+
+#[derive(Debug)]
+enum Token {
+    Text(Cow<'static, str>),
+    Line(Cow<'static, str>),
+    Indent,
+    Dedent,
+    Child { child: Child, min_precedence: u32 },
+}
+
+/// Describes how to print a single frame, as returned by the per-frame callback passed to [`TreePrinter`].
+///
+/// A layout is a sequence of text, children and potential line breaks. If a node (including all of its
+/// children) fits on the current line it's printed flat, otherwise each of its line breaks starts a new line
+/// at the current indentation. Each node makes this choice separately, so a node that doesn't fit can
+/// still have children that are printed flat.
+#[derive(Debug)]
+pub struct Layout {
+    precedence: u32,
+    tokens: Vec<Token>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Layout {
+    /// An empty layout, with the highest possible precedence such that it's never parenthesized
+    pub fn new() -> Self {
+        Self {
+            precedence: u32::MAX,
+            tokens: Vec::new(),
+        }
+    }
+
+    /// A layout in the style of `{:#?}`: `head(first, second)`, with each child on its own indented line
+    /// if it doesn't fit on the current line. Just `head` if there are no children.
+    pub fn tuple(
+        head: impl Into<Cow<'static, str>>,
+        children: impl IntoIterator<Item = Child>,
+    ) -> Self {
+        let mut layout = Self::new().text(head);
+        let mut children = children.into_iter();
+
+        if let Some(first) = children.next() {
+            layout = layout.text("(").indent().line("").child(first);
+            for child in children {
+                layout = layout.text(",").line(" ").child(child);
+            }
+            layout = layout.dedent().line("").text(")");
+        }
+
+        layout
+    }
+
+    /// Set the precedence of this node, as compared against the minimum precedence given via
+    /// [`Layout::child_at`] when deciding whether to parenthesize it
+    pub fn precedence(mut self, precedence: u32) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Append some text, which should not contain newlines
+    pub fn text(mut self, text: impl Into<Cow<'static, str>>) -> Self {
+        self.tokens.push(Token::Text(text.into()));
+        self
+    }
+
+    /// Append a line break, printed as `flat` instead if this node fits on the current line
+    pub fn line(mut self, flat: impl Into<Cow<'static, str>>) -> Self {
+        self.tokens.push(Token::Line(flat.into()));
+        self
+    }
+
+    /// Increase the indentation applied to subsequent line breaks
+    pub fn indent(mut self) -> Self {
+        self.tokens.push(Token::Indent);
+        self
+    }
+
+    /// Decrease the indentation applied to subsequent line breaks
+    pub fn dedent(mut self) -> Self {
+        self.tokens.push(Token::Dedent);
+        self
+    }
+
+    /// Append a child, which is never parenthesized
+    pub fn child(self, child: Child) -> Self {
+        self.child_at(child, 0)
+    }
+
+    /// Append a child, parenthesizing it if its precedence is lower than `min_precedence`
+    pub fn child_at(mut self, child: Child, min_precedence: u32) -> Self {
+        self.tokens.push(Token::Child {
+            child,
+            min_precedence,
+        });
+        self
+    }
+}
+
+/// Stack safe printing for any [`Collapsible`] type, given a callback that describes how to print each frame.
+///
+/// Each frame is laid out via [`CollapsibleExt::collapse_frames`], and the resulting layouts are then printed
+/// using an explicit stack, so this can be used to implement `Debug` or `Display` for arbitrarily deep structures.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum ExprFrame<A> {
+/// #     Add(A, A),
+/// #     Mul(A, A),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+/// #             ExprFrame::Mul(a, b) => ExprFrame::Mul(f(a), f(b)),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// enum Expr {
+///     Add(Box<Expr>, Box<Expr>),
+///     Mul(Box<Expr>, Box<Expr>),
+///     LiteralInt(i64),
+/// }
+///
+/// impl<'a> Collapsible for &'a Expr {
+///     type FrameToken = ExprFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ExprFrame<&'a Expr> {
+///         match self {
+///             Expr::Add(a, b) => ExprFrame::Add(a, b),
+///             Expr::Mul(a, b) => ExprFrame::Mul(a, b),
+///             Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+///         }
+///     }
+/// }
+///
+/// // (1 + 2) * 3
+/// let expr = Expr::Mul(
+///     Box::new(Expr::Add(
+///         Box::new(Expr::LiteralInt(1)),
+///         Box::new(Expr::LiteralInt(2)),
+///     )),
+///     Box::new(Expr::LiteralInt(3)),
+/// );
+///
+/// let infix = TreePrinter::new().print(&expr, |frame| match frame {
+///     ExprFrame::Add(a, b) => Layout::new().precedence(1).child_at(a, 1).text(" + ").child_at(b, 2),
+///     ExprFrame::Mul(a, b) => Layout::new().precedence(2).child_at(a, 2).text(" * ").child_at(b, 3),
+///     ExprFrame::LiteralInt(x) => Layout::new().text(x.to_string()),
+/// });
+/// assert_eq!(infix, "(1 + 2) * 3");
+///
+/// let debug = TreePrinter::new().max_width(20).indent(2).print(&expr, |frame| match frame {
+///     ExprFrame::Add(a, b) => Layout::tuple("Add", [a, b]),
+///     ExprFrame::Mul(a, b) => Layout::tuple("Mul", [a, b]),
+///     ExprFrame::LiteralInt(x) => Layout::tuple(format!("LiteralInt({})", x), []),
+/// });
+/// assert_eq!(
+///     debug,
+///     "Mul(\n  Add(\n    LiteralInt(1),\n    LiteralInt(2)\n  ),\n  LiteralInt(3)\n)"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TreePrinter {
+    max_width: usize,
+    indent: usize,
+}
+
+impl Default for TreePrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePrinter {
+    /// A printer with a maximum width of 80 columns, indenting by 4 spaces
+    pub fn new() -> Self {
+        Self {
+            max_width: 80,
+            indent: 4,
+        }
+    }
+
+    /// Set the maximum width: nodes that would extend past this column are broken across multiple lines
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Set the number of spaces added for each level of indentation
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Print some structure to a new `String`
+    pub fn print<C: Collapsible>(
+        &self,
+        input: C,
+        layout_frame: impl FnMut(<C::FrameToken as MappableFrame>::Frame<Child>) -> Layout,
+    ) -> String {
+        let mut out = String::new();
+        self.write(input, layout_frame, &mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Print some structure to any [`fmt::Write`] implementation, such as a [`fmt::Formatter`]
+    pub fn write<C: Collapsible, W: Write + ?Sized>(
+        &self,
+        input: C,
+        mut layout_frame: impl FnMut(<C::FrameToken as MappableFrame>::Frame<Child>) -> Layout,
+        out: &mut W,
+    ) -> fmt::Result {
+        // lay out each node, along with its width when printed flat
+        let mut nodes: Vec<(Layout, usize)> = Vec::new();
+        let root = input.collapse_frames(|frame| {
+            let layout = layout_frame(frame);
+            let width = layout.tokens.iter().fold(0, |width, token| {
+                width
+                    + match token {
+                        Token::Text(text) | Token::Line(text) => text.chars().count(),
+                        Token::Indent | Token::Dedent => 0,
+                        Token::Child {
+                            child,
+                            min_precedence,
+                        } => {
+                            let (child, child_width) = &nodes[child.0];
+                            child_width
+                                + if child.precedence < *min_precedence {
+                                    2
+                                } else {
+                                    0
+                                }
+                        }
+                    }
+            });
+            nodes.push((layout, width));
+            Child(nodes.len() - 1)
+        });
+
+        let mut column = 0;
+        let mut stack = Vec::new();
+        stack.push(Print::Node {
+            node: root.0,
+            parens: false,
+            indent: 0,
+            flat: false,
+        });
+
+        while let Some(print) = stack.pop() {
+            match print {
+                Print::Node {
+                    node,
+                    parens,
+                    indent,
+                    flat,
+                } => {
+                    // if a node is printed flat, so are all of its children
+                    let width = nodes[node].1 + if parens { 2 } else { 0 };
+                    let flat = flat || column + width <= self.max_width;
+                    if parens {
+                        out.write_char('(')?;
+                        column += 1;
+                        stack.push(Print::CloseParen);
+                    }
+                    stack.push(Print::Tokens {
+                        node,
+                        pos: 0,
+                        indent,
+                        flat,
+                    });
+                }
+                Print::CloseParen => {
+                    out.write_char(')')?;
+                    column += 1;
+                }
+                Print::Tokens {
+                    node,
+                    mut pos,
+                    mut indent,
+                    flat,
+                } => {
+                    let tokens = &nodes[node].0.tokens;
+                    while let Some(token) = tokens.get(pos) {
+                        pos += 1;
+                        match token {
+                            Token::Text(text) => {
+                                out.write_str(text)?;
+                                column += text.chars().count();
+                            }
+                            Token::Line(text) if flat => {
+                                out.write_str(text)?;
+                                column += text.chars().count();
+                            }
+                            Token::Line(_) => {
+                                write_line(out, indent)?;
+                                column = indent;
+                            }
+                            Token::Indent => indent += self.indent,
+                            Token::Dedent => indent = indent.saturating_sub(self.indent),
+                            Token::Child {
+                                child,
+                                min_precedence,
+                            } => {
+                                // resume this node once the child has been printed
+                                stack.push(Print::Tokens {
+                                    node,
+                                    pos,
+                                    indent,
+                                    flat,
+                                });
+                                stack.push(Print::Node {
+                                    node: child.0,
+                                    parens: nodes[child.0].0.precedence < *min_precedence,
+                                    indent,
+                                    flat,
+                                });
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum Print {
+    Node {
+        node: usize,
+        parens: bool,
+        indent: usize,
+        flat: bool,
+    },
+    Tokens {
+        node: usize,
+        pos: usize,
+        indent: usize,
+        flat: bool,
+    },
+    CloseParen,
+}
+
+/// Start a new line, indented by `indent` spaces
+pub(crate) fn write_line<W: Write + ?Sized>(out: &mut W, mut indent: usize) -> fmt::Result {
+    const SPACES: &str = "                                                                ";
+
+    out.write_char('\n')?;
+    while indent > 0 {
+        let spaces = indent.min(SPACES.len());
+        out.write_str(&SPACES[..spaces])?;
+        indent -= spaces;
+    }
+    Ok(())
+}
@@ -1,5 +1,13 @@
 pub mod collapse;
+pub mod convert;
 pub mod expand;
+pub mod linear;
+pub mod payload;
+pub mod zip;
 
 pub use collapse::{Collapsible, CollapsibleExt};
+pub use convert::CollapsibleConvertExt;
 pub use expand::{Expandable, ExpandableExt};
+pub use linear::CollapsibleLinearExt;
+pub use payload::CollapsiblePayloadExt;
+pub use zip::zip_collapse;
@@ -1,4 +1,6 @@
+use crate::algebra::{Algebra, IntoAlgebra, IntoTryAlgebra, TryAlgebra};
 use crate::frame::{expand_and_collapse, MappableFrame};
+use crate::machine::TraversalContext;
 
 /// The ability to recursively collapse some type into some output type, frame by frame.
 ///
@@ -158,7 +160,6 @@ use crate::frame::{expand_and_collapse, MappableFrame};
 ///
 /// assert_eq!(leaf_count, 4)
 /// ```
-
 pub trait Collapsible
 where
     Self: Sized,
@@ -176,41 +177,123 @@ where
 {
     /// Given an instance of this type, collapse it into a single value of type `Out` by
     /// traversing the recursive structure of `self`, generating frames, and collapsing
-    /// those frames using some function from `Frame<Out> -> Out`
-    fn collapse_frames<Out>(
+    /// those frames using some function from `Frame<Out> -> Out`, or more generally some [`Algebra`]
+    fn collapse_frames<M, A: IntoAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
-    ) -> Out;
+        collapse_frame: A,
+    ) -> <A::Algebra as Algebra<Self::FrameToken>>::Out;
 
     /// Given an instance of this type, collapse it into a single value of type `Result<Out, E>` by
     /// traversing the recursive structure of `self`, generating frames, and collapsing
-    /// those frames using some function from `Frame<Out> -> Result<Out, E>`
-    fn try_collapse_frames<Out, E>(
+    /// those frames using some function from `Frame<Out> -> Result<Out, E>`, or more generally some
+    /// [`TryAlgebra`]
+    #[allow(clippy::type_complexity)]
+    fn try_collapse_frames<M, A: IntoTryAlgebra<Self::FrameToken, M>>(
+        self,
+        collapse_frame: A,
+    ) -> Result<
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Out,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Error,
+    >;
+
+    /// Like `collapse_frames`, but reuses the scratch buffers owned by some [`TraversalContext`]
+    /// instead of allocating fresh buffers for this traversal
+    fn collapse_frames_with<M, A: IntoAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Result<Out, E>,
-    ) -> Result<Out, E>;
+        ctx: &mut TraversalContext<
+            Self::FrameToken,
+            Self,
+            <A::Algebra as Algebra<Self::FrameToken>>::Carrier,
+        >,
+        collapse_frame: A,
+    ) -> <A::Algebra as Algebra<Self::FrameToken>>::Out;
+
+    /// Like `try_collapse_frames`, but reuses the scratch buffers owned by some [`TraversalContext`]
+    /// instead of allocating fresh buffers for this traversal
+    #[allow(clippy::type_complexity)]
+    fn try_collapse_frames_with<M, A: IntoTryAlgebra<Self::FrameToken, M>>(
+        self,
+        ctx: &mut TraversalContext<
+            Self::FrameToken,
+            Self,
+            <A::Algebra as TryAlgebra<Self::FrameToken>>::Carrier,
+        >,
+        collapse_frame: A,
+    ) -> Result<
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Out,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Error,
+    >;
 }
 
 impl<X> CollapsibleExt for X
 where
     X: Collapsible,
 {
-    fn collapse_frames<Out>(
+    fn collapse_frames<M, A: IntoAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
-    ) -> Out {
-        expand_and_collapse::<Self::FrameToken, Self, Out>(self, Self::into_frame, collapse_frame)
+        collapse_frame: A,
+    ) -> <A::Algebra as Algebra<Self::FrameToken>>::Out {
+        let mut algebra = collapse_frame.into_algebra();
+        let carrier =
+            expand_and_collapse::<Self::FrameToken, Self, _>(self, Self::into_frame, |frame| {
+                algebra.collapse_frame(frame)
+            });
+        algebra.finish(carrier)
     }
 
     // TODO: add example here in this file
-    fn try_collapse_frames<Out, E>(
+    #[allow(clippy::type_complexity)]
+    fn try_collapse_frames<M, A: IntoTryAlgebra<Self::FrameToken, M>>(
+        self,
+        collapse_frame: A,
+    ) -> Result<
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Out,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Error,
+    > {
+        let mut algebra = collapse_frame.into_try_algebra();
+        let carrier = crate::frame::try_expand_and_collapse::<Self::FrameToken, Self, _, _>(
+            self,
+            |seed| Ok(Self::into_frame(seed)),
+            |frame| algebra.try_collapse_frame(frame),
+        )?;
+        Ok(algebra.finish(carrier))
+    }
+
+    fn collapse_frames_with<M, A: IntoAlgebra<Self::FrameToken, M>>(
+        self,
+        ctx: &mut TraversalContext<
+            Self::FrameToken,
+            Self,
+            <A::Algebra as Algebra<Self::FrameToken>>::Carrier,
+        >,
+        collapse_frame: A,
+    ) -> <A::Algebra as Algebra<Self::FrameToken>>::Out {
+        let mut algebra = collapse_frame.into_algebra();
+        let carrier = ctx.expand_and_collapse(self, Self::into_frame, |frame| {
+            algebra.collapse_frame(frame)
+        });
+        algebra.finish(carrier)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn try_collapse_frames_with<M, A: IntoTryAlgebra<Self::FrameToken, M>>(
         self,
-        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Result<Out, E>,
-    ) -> Result<Out, E> {
-        crate::frame::try_expand_and_collapse::<Self::FrameToken, Self, Out, E>(
+        ctx: &mut TraversalContext<
+            Self::FrameToken,
+            Self,
+            <A::Algebra as TryAlgebra<Self::FrameToken>>::Carrier,
+        >,
+        collapse_frame: A,
+    ) -> Result<
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Out,
+        <A::Algebra as TryAlgebra<Self::FrameToken>>::Error,
+    > {
+        let mut algebra = collapse_frame.into_try_algebra();
+        let carrier = ctx.try_expand_and_collapse(
             self,
             |seed| Ok(Self::into_frame(seed)),
-            collapse_frame,
-        )
+            |frame| algebra.try_collapse_frame(frame),
+        )?;
+        Ok(algebra.finish(carrier))
     }
 }
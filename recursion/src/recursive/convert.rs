@@ -0,0 +1,46 @@
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::{Collapsible, CollapsibleExt};
+use crate::recursive::expand::Expandable;
+
+/// Conversions from some [`Collapsible`] type into some [`Expandable`] type, one frame at a time.
+pub trait CollapsibleConvertExt: Collapsible {
+    /// Convert an instance of this type into some [`Expandable`] type one frame at a time, using some
+    /// function from this type's frames to the target type's frames. This fuses collapsing `self` and
+    /// expanding the target into a single stack-safe pass, with no intermediate structure.
+    fn convert_frames<Target: Expandable>(
+        self,
+        convert_frame: impl FnMut(
+            <Self::FrameToken as MappableFrame>::Frame<Target>,
+        ) -> <Target::FrameToken as MappableFrame>::Frame<Target>,
+    ) -> Target;
+
+    /// Like `convert_frames`, but with a fallible conversion function: stops at the first error
+    fn try_convert_frames<Target: Expandable, E>(
+        self,
+        convert_frame: impl FnMut(
+            <Self::FrameToken as MappableFrame>::Frame<Target>,
+        )
+            -> Result<<Target::FrameToken as MappableFrame>::Frame<Target>, E>,
+    ) -> Result<Target, E>;
+}
+
+impl<X: Collapsible> CollapsibleConvertExt for X {
+    fn convert_frames<Target: Expandable>(
+        self,
+        mut convert_frame: impl FnMut(
+            <Self::FrameToken as MappableFrame>::Frame<Target>,
+        ) -> <Target::FrameToken as MappableFrame>::Frame<Target>,
+    ) -> Target {
+        self.collapse_frames(|frame| Target::from_frame(convert_frame(frame)))
+    }
+
+    fn try_convert_frames<Target: Expandable, E>(
+        self,
+        mut convert_frame: impl FnMut(
+            <Self::FrameToken as MappableFrame>::Frame<Target>,
+        )
+            -> Result<<Target::FrameToken as MappableFrame>::Frame<Target>, E>,
+    ) -> Result<Target, E> {
+        self.try_collapse_frames(|frame| convert_frame(frame).map(Target::from_frame))
+    }
+}
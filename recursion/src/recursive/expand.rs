@@ -1,4 +1,6 @@
+use crate::algebra::{Coalgebra, IntoCoalgebra};
 use crate::frame::{expand_and_collapse, MappableFrame};
+use crate::machine::TraversalContext;
 
 /// The ability to recursively expand a seed to construct a value of this type, frame by frame.
 ///
@@ -154,7 +156,6 @@ use crate::frame::{expand_and_collapse, MappableFrame};
 ///
 /// assert_eq!(expected, expanded_tree)
 /// ```
-
 pub trait Expandable
 where
     Self: Sized,
@@ -167,18 +168,50 @@ where
 
 pub trait ExpandableExt: Expandable {
     /// Given a value of type `In`, expand it to generate a value of type `Self` frame by frame,
-    /// using a function from `In -> Frame<In>`
-    fn expand_frames<In>(
+    /// using a function from `In -> Frame<In>`, or more generally some [`Coalgebra`]
+    fn expand_frames<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
+        input: In,
+        expand_frame: C,
+    ) -> Self;
+
+    /// Like `expand_frames`, but reuses the scratch buffers owned by some [`TraversalContext`]
+    /// instead of allocating fresh buffers for this traversal
+    fn expand_frames_with<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
         input: In,
-        expand_frame: impl FnMut(In) -> <Self::FrameToken as MappableFrame>::Frame<In>,
+        ctx: &mut TraversalContext<
+            Self::FrameToken,
+            <C::Coalgebra as Coalgebra<Self::FrameToken>>::Seed,
+            Self,
+        >,
+        expand_frame: C,
     ) -> Self;
 }
 
 impl<X: Expandable> ExpandableExt for X {
-    fn expand_frames<In>(
+    fn expand_frames<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
+        input: In,
+        expand_frame: C,
+    ) -> Self {
+        let mut coalgebra = expand_frame.into_coalgebra();
+        let seed = coalgebra.start(input);
+        expand_and_collapse::<Self::FrameToken, _, Self>(
+            seed,
+            |seed| coalgebra.expand_frame(seed),
+            Self::from_frame,
+        )
+    }
+
+    fn expand_frames_with<In, M, C: IntoCoalgebra<Self::FrameToken, In, M>>(
         input: In,
-        expand_frame: impl FnMut(In) -> <Self::FrameToken as MappableFrame>::Frame<In>,
+        ctx: &mut TraversalContext<
+            Self::FrameToken,
+            <C::Coalgebra as Coalgebra<Self::FrameToken>>::Seed,
+            Self,
+        >,
+        expand_frame: C,
     ) -> Self {
-        expand_and_collapse::<Self::FrameToken, In, Self>(input, expand_frame, Self::from_frame)
+        let mut coalgebra = expand_frame.into_coalgebra();
+        let seed = coalgebra.start(input);
+        ctx.expand_and_collapse(seed, |seed| coalgebra.expand_frame(seed), Self::from_frame)
     }
 }
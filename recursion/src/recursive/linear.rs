@@ -0,0 +1,149 @@
+use alloc::vec::Vec;
+
+use crate::frame::{LinearFrame, MappableFrame};
+use crate::recursive::collapse::Collapsible;
+
+/// Specialized traversals for [`Collapsible`] types whose frames hold at most one recursive position
+/// (see [`LinearFrame`]), such as linked lists.
+///
+/// A linear structure is a chain of frames, so there's no need for the general machinery used by
+/// [`crate::CollapsibleExt::collapse_frames`]: a left fold runs in constant space, much like [`Iterator::fold`],
+/// and a right fold only needs to keep the chain of frames itself.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum ListFrame<Elem, Next> {
+/// #     Cons(Elem, Next),
+/// #     Nil,
+/// # }
+/// # impl<Elem> MappableFrame for ListFrame<Elem, PartiallyApplied> {
+/// #     type Frame<Next> = ListFrame<Elem, Next>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ListFrame::Cons(elem, next) => ListFrame::Cons(elem, f(next)),
+/// #             ListFrame::Nil => ListFrame::Nil,
+/// #         }
+/// #     }
+/// # }
+/// # impl<Elem> LinearFrame for ListFrame<Elem, PartiallyApplied> {}
+/// struct Slice<'a>(&'a [char]);
+///
+/// impl<'a> Collapsible for Slice<'a> {
+///     type FrameToken = ListFrame<&'a char, PartiallyApplied>;
+///
+///     fn into_frame(self) -> <Self::FrameToken as MappableFrame>::Frame<Self> {
+///         match self.0.split_first() {
+///             Some((first, rest)) => ListFrame::Cons(first, Slice(rest)),
+///             None => ListFrame::Nil,
+///         }
+///     }
+/// }
+///
+/// let chars = ['a', 'b', 'c'];
+///
+/// // visits frames from the outermost to the innermost, threading an accumulator through
+/// let left = Slice(&chars).fold_frames_left(String::new(), |mut acc, frame: ListFrame<_, ()>| {
+///     match frame {
+///         ListFrame::Cons(c, ()) => acc.push(*c),
+///         ListFrame::Nil => acc.push('.'),
+///     }
+///     acc
+/// });
+///
+/// // collapses frames from the innermost to the outermost, like `collapse_frames`
+/// let right = Slice(&chars).fold_frames_right(|frame: ListFrame<_, String>| match frame {
+///     ListFrame::Cons(c, mut acc) => {
+///         acc.push(*c);
+///         acc
+///     }
+///     ListFrame::Nil => ".".to_string(),
+/// });
+///
+/// assert_eq!(left, "abc.");
+/// assert_eq!(right, ".cba");
+/// ```
+pub trait CollapsibleLinearExt: Collapsible
+where
+    Self::FrameToken: LinearFrame,
+{
+    /// Given an instance of this type, fold it into a single value of type `Acc`, starting from `init`
+    /// and visiting each frame in turn from the outermost to the innermost. Frames are passed
+    /// with their recursive position (if any) replaced by `()`.
+    ///
+    /// This runs in constant space.
+    fn fold_frames_left<Acc>(
+        self,
+        init: Acc,
+        fold_frame: impl FnMut(Acc, <Self::FrameToken as MappableFrame>::Frame<()>) -> Acc,
+    ) -> Acc;
+
+    /// Given an instance of this type, collapse it into a single value of type `Out` using some
+    /// function from `Frame<Out> -> Out`, starting from the innermost frame. This computes the same
+    /// result as [`crate::CollapsibleExt::collapse_frames`], but without any of the bookkeeping
+    /// required for frames with multiple recursive positions.
+    fn fold_frames_right<Out>(
+        self,
+        collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
+    ) -> Out;
+}
+
+impl<X> CollapsibleLinearExt for X
+where
+    X: Collapsible,
+    X::FrameToken: LinearFrame,
+{
+    fn fold_frames_left<Acc>(
+        self,
+        init: Acc,
+        mut fold_frame: impl FnMut(Acc, <Self::FrameToken as MappableFrame>::Frame<()>) -> Acc,
+    ) -> Acc {
+        let mut acc = init;
+        let mut next = Some(self);
+
+        while let Some(current) = next.take() {
+            let frame = split_frame::<Self::FrameToken, Self>(current.into_frame(), &mut next);
+            acc = fold_frame(acc, frame);
+        }
+
+        acc
+    }
+
+    fn fold_frames_right<Out>(
+        self,
+        mut collapse_frame: impl FnMut(<Self::FrameToken as MappableFrame>::Frame<Out>) -> Out,
+    ) -> Out {
+        let mut frames = Vec::new();
+        let mut next = Some(self);
+
+        while let Some(current) = next.take() {
+            frames.push(split_frame::<Self::FrameToken, Self>(
+                current.into_frame(),
+                &mut next,
+            ));
+        }
+
+        let mut out = None;
+        for frame in frames.into_iter().rev() {
+            let frame = <Self::FrameToken as MappableFrame>::map_frame(frame, |()| {
+                out.take()
+                    .expect("LinearFrame::map_frame visited more than one recursive position")
+            });
+            out = Some(collapse_frame(frame));
+        }
+
+        // the chain always holds at least one frame, so there's always an output
+        out.unwrap()
+    }
+}
+
+#[inline(always)]
+fn split_frame<F: LinearFrame, X>(frame: F::Frame<X>, next: &mut Option<X>) -> F::Frame<()> {
+    F::map_frame(frame, |child| {
+        assert!(
+            next.replace(child).is_none(),
+            "LinearFrame::map_frame visited more than one recursive position"
+        );
+    })
+}
@@ -0,0 +1,108 @@
+use crate::frame::BimappableFrame;
+use crate::recursive::collapse::Collapsible;
+use crate::recursive::expand::{Expandable, ExpandableExt};
+
+/// Map over the payloads of [`Collapsible`] types whose frames have a payload type parameter
+/// (see [`BimappableFrame`]), such as the elements of a list or the values stored in a tree.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum TreeFrame<V, A> {
+/// #     Leaf(V),
+/// #     Node(A, A),
+/// # }
+/// # impl<V> MappableFrame for TreeFrame<V, PartiallyApplied> {
+/// #     type Frame<X> = TreeFrame<V, X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             TreeFrame::Leaf(v) => TreeFrame::Leaf(v),
+/// #             TreeFrame::Node(a, b) => TreeFrame::Node(f(a), f(b)),
+/// #         }
+/// #     }
+/// # }
+/// impl<V> BimappableFrame for TreeFrame<V, PartiallyApplied> {
+///     type Payload = V;
+///     type WithPayload<P> = TreeFrame<P, PartiallyApplied>;
+///
+///     fn bimap_frame<A, B, P>(
+///         input: Self::Frame<A>,
+///         mut payload: impl FnMut(V) -> P,
+///         mut f: impl FnMut(A) -> B,
+///     ) -> TreeFrame<P, B> {
+///         match input {
+///             TreeFrame::Leaf(v) => TreeFrame::Leaf(payload(v)),
+///             TreeFrame::Node(a, b) => TreeFrame::Node(f(a), f(b)),
+///         }
+///     }
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Tree<V> {
+///     Leaf(V),
+///     Node(Box<Tree<V>>, Box<Tree<V>>),
+/// }
+///
+/// impl<'a, V> Collapsible for &'a Tree<V> {
+///     type FrameToken = TreeFrame<&'a V, PartiallyApplied>;
+///
+///     fn into_frame(self) -> TreeFrame<&'a V, &'a Tree<V>> {
+///         match self {
+///             Tree::Leaf(v) => TreeFrame::Leaf(v),
+///             Tree::Node(a, b) => TreeFrame::Node(a, b),
+///         }
+///     }
+/// }
+///
+/// impl<V> Expandable for Tree<V> {
+///     type FrameToken = TreeFrame<V, PartiallyApplied>;
+///
+///     fn from_frame(frame: TreeFrame<V, Self>) -> Self {
+///         match frame {
+///             TreeFrame::Leaf(v) => Tree::Leaf(v),
+///             TreeFrame::Node(a, b) => Tree::Node(Box::new(a), Box::new(b)),
+///         }
+///     }
+/// }
+///
+/// let ints = Tree::Node(Box::new(Tree::Leaf(1)), Box::new(Tree::Leaf(2)));
+/// let floats: Tree<f64> = (&ints).map_payload(|x: &i64| *x as f64 / 2.0);
+/// assert_eq!(floats, Tree::Node(Box::new(Tree::Leaf(0.5)), Box::new(Tree::Leaf(1.0))));
+///
+/// let words = Tree::Node(Box::new(Tree::Leaf("a")), Box::new(Tree::Leaf("b")));
+/// let owned: Tree<String> = (&words).map_payload(|s: &&str| s.to_string());
+/// assert_eq!(owned, Tree::Node(Box::new(Tree::Leaf("a".to_string())), Box::new(Tree::Leaf("b".to_string()))));
+/// ```
+pub trait CollapsiblePayloadExt: Collapsible
+where
+    Self::FrameToken: BimappableFrame,
+{
+    /// Given an instance of this type, build some [`Expandable`] type with the same structure, applying
+    /// `payload` to each payload along the way. This doesn't recurse, so it's safe to use on arbitrarily
+    /// deep structures.
+    fn map_payload<Target, P>(
+        self,
+        payload: impl FnMut(<Self::FrameToken as BimappableFrame>::Payload) -> P,
+    ) -> Target
+    where
+        Target: Expandable<FrameToken = <Self::FrameToken as BimappableFrame>::WithPayload<P>>;
+}
+
+impl<X> CollapsiblePayloadExt for X
+where
+    X: Collapsible,
+    X::FrameToken: BimappableFrame,
+{
+    fn map_payload<Target, P>(
+        self,
+        mut payload: impl FnMut(<Self::FrameToken as BimappableFrame>::Payload) -> P,
+    ) -> Target
+    where
+        Target: Expandable<FrameToken = <Self::FrameToken as BimappableFrame>::WithPayload<P>>,
+    {
+        Target::expand_frames(self, |node: Self| {
+            <Self::FrameToken as BimappableFrame>::map_payload(node.into_frame(), &mut payload)
+        })
+    }
+}
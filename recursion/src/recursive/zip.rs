@@ -0,0 +1,84 @@
+use core::marker::PhantomData;
+
+use crate::frame::{expand_and_collapse, MappableFrame, ZippableFrame};
+use crate::recursive::collapse::Collapsible;
+
+/// Collapse two [`Collapsible`] values of the same frame type in lockstep, one pair of frames at a time.
+///
+/// At each step the frames produced by `left` and `right` are zipped via [`ZippableFrame::zip_frames`].
+/// Frames with the same shape are traversed together and collapsed with `collapse_frame`. When the shapes
+/// differ, traversal of that branch stops and `on_mismatch` is called with both frames to produce a
+/// result for it directly - it's free to ignore the remaining children, or to collapse them separately.
+///
+/// Like [`crate::CollapsibleExt::collapse_frames`], this function is stack safe.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// struct Nat(usize);
+///
+/// impl Collapsible for Nat {
+///     type FrameToken = Option<PartiallyApplied>;
+///
+///     fn into_frame(self) -> Option<Nat> {
+///         self.0.checked_sub(1).map(Nat)
+///     }
+/// }
+///
+/// // the depth at which two numbers stop having the same shape is the smaller of the two
+/// let min = zip_collapse(
+///     Nat(3),
+///     Nat(5),
+///     |frame: Option<usize>| frame.map_or(0, |n| n + 1),
+///     |_, _| 0,
+/// );
+///
+/// assert_eq!(min, 3);
+/// ```
+pub fn zip_collapse<Left, Right, Out>(
+    left: Left,
+    right: Right,
+    mut collapse_frame: impl FnMut(<Left::FrameToken as MappableFrame>::Frame<Out>) -> Out,
+    mut on_mismatch: impl FnMut(
+        <Left::FrameToken as MappableFrame>::Frame<Left>,
+        <Left::FrameToken as MappableFrame>::Frame<Right>,
+    ) -> Out,
+) -> Out
+where
+    Left: Collapsible,
+    Right: Collapsible<FrameToken = Left::FrameToken>,
+    Left::FrameToken: ZippableFrame,
+{
+    expand_and_collapse::<ZipStep<Left::FrameToken, Out>, (Left, Right), Out>(
+        (left, right),
+        |(left, right)| match Left::FrameToken::zip_frames(left.into_frame(), right.into_frame()) {
+            Ok(zipped) => ZipStepFrame::Zipped(zipped),
+            Err((left, right)) => ZipStepFrame::Mismatch(on_mismatch(left, right)),
+        },
+        |frame| match frame {
+            ZipStepFrame::Zipped(zipped) => collapse_frame(zipped),
+            ZipStepFrame::Mismatch(out) => out,
+        },
+    )
+}
+
+/// A single step of a zipped traversal: either a zipped frame, or the result of handling a mismatch
+enum ZipStepFrame<Zipped, Mismatch> {
+    Zipped(Zipped),
+    Mismatch(Mismatch),
+}
+
+/// Marker type for [`ZipStepFrame`], with mismatches acting as leaves
+struct ZipStep<F, Mismatch>(PhantomData<(F, Mismatch)>);
+
+impl<F: MappableFrame, Mismatch> MappableFrame for ZipStep<F, Mismatch> {
+    type Frame<X> = ZipStepFrame<F::Frame<X>, Mismatch>;
+
+    fn map_frame<A, B>(input: Self::Frame<A>, f: impl FnMut(A) -> B) -> Self::Frame<B> {
+        match input {
+            ZipStepFrame::Zipped(zipped) => ZipStepFrame::Zipped(F::map_frame(zipped, f)),
+            ZipStepFrame::Mismatch(out) => ZipStepFrame::Mismatch(out),
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::Collapsible;
+use crate::recursive::expand::Expandable;
+
+/// The order in which a [`Rewriter`] applies its rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// A single pass from the leaves up: each node is rewritten at most once, after its children.
+    /// The results of rules are not rewritten any further.
+    BottomUp,
+    /// A single pass from the root down: each node is rewritten at most once, before its children.
+    /// If a rule fires, the children of its result are visited instead.
+    TopDown,
+    /// Rewrite to a fixpoint, innermost redexes first: each node is rewritten after its children have
+    /// been fully rewritten, and the root of each rule's result is itself rewritten until no rule fires.
+    Innermost,
+    /// Rewrite to a fixpoint, outermost redexes first: rules are applied to each node until none fire
+    /// before visiting its children, and again afterwards in case rewriting its children created a redex.
+    Outermost,
+}
+
+/// The result of a [`Rewriter`] pass
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Rewritten<T> {
+    /// The rewritten term
+    pub term: T,
+    /// The name of each rule that fired, in the order they fired
+    pub trace: Vec<&'static str>,
+}
+
+/// Returned by [`Rewriter::rewrite`] when the rewrite limit is exceeded. Holds the term as rewritten so far,
+/// including the rewrite that went over the limit, with no further rules applied after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitExceeded<T>(pub Rewritten<T>);
+
+impl<T> fmt::Display for LimitExceeded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rewrite limit of {} exceeded",
+            self.0.trace.len().saturating_sub(1)
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> std::error::Error for LimitExceeded<T> {}
+
+type Rule<'r, F, T> =
+    Box<dyn Fn(<F as MappableFrame>::Frame<T>) -> Result<T, <F as MappableFrame>::Frame<T>> + 'r>;
+
+/// A term-rewriting engine for types that are both [`Collapsible`] and [`Expandable`] via the same frame type.
+///
+/// Rules take a frame (holding already-built children) by value, and either return a replacement term or
+/// hand the frame back unchanged. They're tried in the order they were added, with the first rule that
+/// returns `Ok` firing, so rules can move children into their result rather than cloning them. Rules are
+/// applied according to some [`Strategy`], stack safely, until either the strategy's pass completes or the
+/// rewrite limit is exceeded.
+///
+/// The fixpoint strategies only rewrite the root of each rule's result again, taking its children to be
+/// in normal form already, so each rewrite costs time proportional to the size of a single frame. Rules
+/// should build their results from the already-rewritten children they're given: new nodes a rule creates
+/// below the root of its result aren't rewritten any further.
+///
+/// # Example
+///
+/// ```rust
+/// # use recursion::*;
+/// # #[derive(Debug, Clone, PartialEq)]
+/// # enum ExprFrame<A> {
+/// #     Add(A, A),
+/// #     Mul(A, A),
+/// #     Var(&'static str),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+/// #             ExprFrame::Mul(a, b) => ExprFrame::Mul(f(a), f(b)),
+/// #             ExprFrame::Var(v) => ExprFrame::Var(v),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Expr(Box<ExprFrame<Expr>>);
+///
+/// impl Collapsible for Expr {
+///     type FrameToken = ExprFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ExprFrame<Expr> {
+///         *self.0
+///     }
+/// }
+///
+/// impl Expandable for Expr {
+///     type FrameToken = ExprFrame<PartiallyApplied>;
+///
+///     fn from_frame(frame: ExprFrame<Expr>) -> Expr {
+///         Expr(Box::new(frame))
+///     }
+/// }
+///
+/// fn lit(x: i64) -> Expr { Expr::from_frame(ExprFrame::LiteralInt(x)) }
+/// fn var(v: &'static str) -> Expr { Expr::from_frame(ExprFrame::Var(v)) }
+/// fn add(a: Expr, b: Expr) -> Expr { Expr::from_frame(ExprFrame::Add(a, b)) }
+/// fn mul(a: Expr, b: Expr) -> Expr { Expr::from_frame(ExprFrame::Mul(a, b)) }
+///
+/// let simplify = Rewriter::new(Strategy::Innermost)
+///     .rule_ref("fold", |frame: &ExprFrame<Expr>| match frame {
+///         ExprFrame::Add(a, b) => match (&*a.0, &*b.0) {
+///             (ExprFrame::LiteralInt(x), ExprFrame::LiteralInt(y)) => Some(lit(x + y)),
+///             _ => None,
+///         },
+///         _ => None,
+///     })
+///     .rule("times one", |frame: ExprFrame<Expr>| match frame {
+///         ExprFrame::Mul(a, b) if *b.0 == ExprFrame::LiteralInt(1) => Ok(a),
+///         frame => Err(frame),
+///     });
+///
+/// // x * (0 + 1)
+/// let rewritten = simplify.rewrite(mul(var("x"), add(lit(0), lit(1)))).unwrap();
+/// assert_eq!(rewritten.term, var("x"));
+/// assert_eq!(rewritten.trace, ["fold", "times one"]);
+/// ```
+pub struct Rewriter<'r, F: MappableFrame, T> {
+    rules: Vec<(&'static str, Rule<'r, F, T>)>,
+    strategy: Strategy,
+    limit: usize,
+}
+
+impl<'r, F, T> Rewriter<'r, F, T>
+where
+    F: MappableFrame,
+    T: Collapsible<FrameToken = F> + Expandable<FrameToken = F>,
+{
+    /// A rewriter with no rules, using the given strategy and a limit of one million rewrites
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            rules: Vec::new(),
+            strategy,
+            limit: 1_000_000,
+        }
+    }
+
+    /// Add a rule, tried after any rules added before it. The name is recorded in the trace when it fires.
+    pub fn rule(
+        mut self,
+        name: &'static str,
+        rule: impl Fn(F::Frame<T>) -> Result<T, F::Frame<T>> + 'r,
+    ) -> Self {
+        self.rules.push((name, Box::new(rule)));
+        self
+    }
+
+    /// Add a rule that inspects its frame by reference, returning a replacement term if it fires.
+    /// Convenient for rules that build their result from scratch rather than from the frame's children.
+    pub fn rule_ref(
+        self,
+        name: &'static str,
+        rule: impl Fn(&F::Frame<T>) -> Option<T> + 'r,
+    ) -> Self {
+        self.rule(name, move |frame| rule(&frame).ok_or(frame))
+    }
+
+    /// Set the maximum number of rewrites. As rules consume their frame, the first rewrite over the limit
+    /// is kept, after which no more rules are applied and the pass returns [`LimitExceeded`].
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Rewrite some term, returning the rewritten term along with a trace of the rules that fired.
+    /// If the rewrite limit is exceeded, returns the term as rewritten up to that point as an error.
+    pub fn rewrite(&self, term: T) -> Result<Rewritten<T>, LimitExceeded<T>> {
+        let (pre, post) = match self.strategy {
+            Strategy::BottomUp => (Pre::Never, Post::Keep),
+            Strategy::TopDown => (Pre::Once, Post::Never),
+            Strategy::Innermost => (Pre::Never, Post::Revisit),
+            Strategy::Outermost => (Pre::Fixpoint, Post::Revisit),
+        };
+
+        let mut pass = Pass {
+            rewriter: self,
+            trace: Vec::new(),
+            exceeded: false,
+        };
+        let mut tasks = Vec::new();
+        let mut values: Vec<T> = Vec::new();
+        let mut children = Vec::new();
+        tasks.push(Task::Visit(term));
+
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Visit(node) => {
+                    let mut frame = node.into_frame();
+                    if pre != Pre::Never {
+                        loop {
+                            match pass.apply(frame) {
+                                Ok(rewritten) => frame = rewritten.into_frame(),
+                                Err(unchanged) => {
+                                    frame = unchanged;
+                                    break;
+                                }
+                            }
+                            if pre == Pre::Once {
+                                break;
+                            }
+                        }
+                    }
+
+                    let shape = F::map_frame(frame, |child| children.push(child));
+                    tasks.push(Task::Build(shape, children.len()));
+                    tasks.extend(children.drain(..).rev().map(Task::Visit));
+                }
+                Task::Build(shape, len) => {
+                    let mut built = values.drain(values.len() - len..);
+                    let frame = F::map_frame(shape, |()| {
+                        built
+                            .next()
+                            .expect("map_frame visited a different number of positions")
+                    });
+                    drop(built);
+
+                    match post {
+                        Post::Never => values.push(T::from_frame(frame)),
+                        Post::Keep => values.push(pass.apply(frame).unwrap_or_else(T::from_frame)),
+                        Post::Revisit => match pass.apply(frame) {
+                            // only the root of the result is rewritten again, its children are
+                            // already in normal form and go straight onto the value stack
+                            Ok(rewritten) => {
+                                let shape = F::map_frame(rewritten.into_frame(), |child| {
+                                    children.push(child)
+                                });
+                                tasks.push(Task::Build(shape, children.len()));
+                                values.append(&mut children);
+                            }
+                            Err(unchanged) => values.push(T::from_frame(unchanged)),
+                        },
+                    }
+                }
+            }
+        }
+
+        let rewritten = Rewritten {
+            term: values.pop().expect("rewriting produced no term"),
+            trace: pass.trace,
+        };
+        if pass.exceeded {
+            Err(LimitExceeded(rewritten))
+        } else {
+            Ok(rewritten)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pre {
+    Never,
+    Once,
+    Fixpoint,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Post {
+    Never,
+    /// apply rules, keeping their results as-is
+    Keep,
+    /// apply rules, rewriting their results in turn
+    Revisit,
+}
+
+enum Task<T, Shape> {
+    Visit(T),
+    /// rebuild a node from its shape and the given number of children, from the top of the value stack
+    Build(Shape, usize),
+}
+
+struct Pass<'a, 'r, F: MappableFrame, T> {
+    rewriter: &'a Rewriter<'r, F, T>,
+    trace: Vec<&'static str>,
+    exceeded: bool,
+}
+
+impl<F: MappableFrame, T> Pass<'_, '_, F, T> {
+    /// Try each rule in turn, returning the result of the first that fires or the unchanged frame
+    fn apply(&mut self, mut frame: F::Frame<T>) -> Result<T, F::Frame<T>> {
+        if self.exceeded {
+            return Err(frame);
+        }
+
+        for (name, rule) in &self.rewriter.rules {
+            match rule(frame) {
+                Ok(rewritten) => {
+                    self.trace.push(name);
+                    // the rule has already consumed the frame, so a rewrite over the limit is kept
+                    self.exceeded = self.trace.len() > self.rewriter.limit;
+                    return Ok(rewritten);
+                }
+                Err(unchanged) => frame = unchanged,
+            }
+        }
+        Err(frame)
+    }
+}
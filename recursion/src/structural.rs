@@ -0,0 +1,212 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::Collapsible;
+use crate::recursive::expand::{Expandable, ExpandableExt};
+
+/// Stack safe structural equality: two values are equal if they produce equal frames (with child positions
+/// erased, so that only their heads are compared) at each position of a pre-order traversal.
+///
+/// Traversal stops at the first pair of frames that differ.
+///
+/// ```rust
+/// # use recursion::*;
+/// struct Nat(usize);
+///
+/// impl Collapsible for Nat {
+///     type FrameToken = Option<PartiallyApplied>;
+///
+///     fn into_frame(self) -> Option<Nat> {
+///         self.0.checked_sub(1).map(Nat)
+///     }
+/// }
+///
+/// assert!(eq_frames(Nat(100_000), Nat(100_000)));
+/// assert!(!eq_frames(Nat(100_000), Nat(99_999)));
+/// ```
+pub fn eq_frames<A, B>(a: A, b: B) -> bool
+where
+    A: Collapsible,
+    B: Collapsible<FrameToken = A::FrameToken>,
+    <A::FrameToken as MappableFrame>::Frame<()>: PartialEq,
+{
+    lockstep(a, b, |a, b| {
+        if a == b {
+            Ordering::Equal
+        } else {
+            Ordering::Less
+        }
+    })
+    .is_eq()
+}
+
+/// Stack safe structural ordering: values are compared frame by frame in pre-order, via the ordering
+/// of their frames (with child positions erased), such that the first pair of frames that differ
+/// determines the result.
+///
+/// Note that this compares the non-recursive data of a node before any of its children, which need not
+/// match the ordering derived for a recursive type that interleaves children and other fields.
+pub fn cmp_frames<A, B>(a: A, b: B) -> Ordering
+where
+    A: Collapsible,
+    B: Collapsible<FrameToken = A::FrameToken>,
+    <A::FrameToken as MappableFrame>::Frame<()>: Ord,
+{
+    lockstep(a, b, Ord::cmp)
+}
+
+/// Stack safe structural hashing: feeds each frame (with child positions erased) into `state` in pre-order.
+/// Consistent with [`eq_frames`], as long as the frames' own `Hash` and `PartialEq` implementations are.
+pub fn hash_frames<C, H>(input: C, state: &mut H)
+where
+    C: Collapsible,
+    <C::FrameToken as MappableFrame>::Frame<()>: Hash,
+    H: Hasher,
+{
+    let mut stack = Vec::new();
+    let mut children = Vec::new();
+    stack.push(input);
+
+    while let Some(node) = stack.pop() {
+        let frame = C::FrameToken::map_frame(node.into_frame(), |child| children.push(child));
+        frame.hash(state);
+        stack.extend(children.drain(..).rev());
+    }
+}
+
+/// Stack safe structural cloning, for types that are [`Collapsible`] by reference and [`Expandable`] via
+/// the same frame type: `&T` is expanded back into a `T` one frame at a time, via [`ExpandableExt::expand_frames`].
+pub fn clone_frames<'a, T>(input: &'a T) -> T
+where
+    T: Expandable,
+    &'a T: Collapsible<FrameToken = T::FrameToken>,
+{
+    T::expand_frames(input, <&'a T>::into_frame)
+}
+
+/// Walk two structures in pre-order, comparing their frames until some pair doesn't compare as equal
+fn lockstep<A, B>(
+    a: A,
+    b: B,
+    mut cmp_frame: impl FnMut(
+        &<A::FrameToken as MappableFrame>::Frame<()>,
+        &<A::FrameToken as MappableFrame>::Frame<()>,
+    ) -> Ordering,
+) -> Ordering
+where
+    A: Collapsible,
+    B: Collapsible<FrameToken = A::FrameToken>,
+{
+    let mut stack = Vec::new();
+    let mut a_children = Vec::new();
+    let mut b_children = Vec::new();
+    stack.push((a, b));
+
+    while let Some((a, b)) = stack.pop() {
+        let a = A::FrameToken::map_frame(a.into_frame(), |child| a_children.push(child));
+        let b = A::FrameToken::map_frame(b.into_frame(), |child| b_children.push(child));
+
+        let ordering = cmp_frame(&a, &b).then(a_children.len().cmp(&b_children.len()));
+        if ordering.is_ne() {
+            return ordering;
+        }
+
+        stack.extend(a_children.drain(..).zip(b_children.drain(..)).rev());
+    }
+
+    Ordering::Equal
+}
+
+/// Implement some of `PartialEq`, `Eq`, `PartialOrd`, `Ord`, `Hash` and `Clone` for a type by delegating to
+/// [`eq_frames`], [`cmp_frames`], [`hash_frames`] and [`clone_frames`], for types where `&T` is [`Collapsible`]
+/// (and, for `Clone`, where `T` is [`Expandable`]).
+///
+/// Unlike the derived implementations, these don't recurse, so they can't overflow the stack
+/// on deeply nested values.
+///
+/// ```rust
+/// # use recursion::*;
+/// #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// enum ListFrame<A> {
+///     Cons(u8, A),
+///     Nil,
+/// }
+///
+/// # impl MappableFrame for ListFrame<PartiallyApplied> {
+/// #     type Frame<X> = ListFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ListFrame::Cons(x, next) => ListFrame::Cons(x, f(next)),
+/// #             ListFrame::Nil => ListFrame::Nil,
+/// #         }
+/// #     }
+/// # }
+/// #[derive(Debug)]
+/// enum List {
+///     Cons(u8, Box<List>),
+///     Nil,
+/// }
+///
+/// impl<'a> Collapsible for &'a List {
+///     type FrameToken = ListFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ListFrame<&'a List> {
+///         match self {
+///             List::Cons(x, next) => ListFrame::Cons(*x, next),
+///             List::Nil => ListFrame::Nil,
+///         }
+///     }
+/// }
+///
+/// recursion::impl_structural_traits!(List: PartialEq, Eq, PartialOrd, Ord, Hash);
+///
+/// let list = |xs: &[u8]| xs.iter().rev().fold(List::Nil, |acc, x| List::Cons(*x, Box::new(acc)));
+/// assert_eq!(list(&[1, 2, 3]), list(&[1, 2, 3]));
+/// assert!(list(&[1, 2, 3]) < list(&[1, 3]));
+/// ```
+#[macro_export]
+macro_rules! impl_structural_traits {
+    ($ty:ty: $($trait:ident),+ $(,)?) => {
+        $( $crate::impl_structural_traits!(@impl $trait for $ty); )+
+    };
+    (@impl PartialEq for $ty:ty) => {
+        impl ::core::cmp::PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::eq_frames(self, other)
+            }
+        }
+    };
+    (@impl Eq for $ty:ty) => {
+        impl ::core::cmp::Eq for $ty {}
+    };
+    (@impl PartialOrd for $ty:ty) => {
+        impl ::core::cmp::PartialOrd for $ty {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::option::Option::Some($crate::cmp_frames(self, other))
+            }
+        }
+    };
+    (@impl Ord for $ty:ty) => {
+        impl ::core::cmp::Ord for $ty {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                $crate::cmp_frames(self, other)
+            }
+        }
+    };
+    (@impl Clone for $ty:ty) => {
+        impl ::core::clone::Clone for $ty {
+            fn clone(&self) -> Self {
+                $crate::clone_frames(self)
+            }
+        }
+    };
+    (@impl Hash for $ty:ty) => {
+        impl ::core::hash::Hash for $ty {
+            fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                $crate::hash_frames(self, state)
+            }
+        }
+    };
+}
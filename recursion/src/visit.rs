@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+
+use crate::frame::MappableFrame;
+use crate::recursive::collapse::Collapsible;
+
+/// Visit and update every node of some structure in place, via its `&mut T` [`Collapsible`] instance,
+/// without recursing and without rebuilding the structure.
+///
+/// Nodes are visited in pre-order: `f` is called on each node before any of its children, which are
+/// then visited in the order given by `map_frame`. As each node's children are only taken from it after
+/// `f` returns, any changes `f` makes to them (such as replacing a subtree) are reflected in the traversal.
+///
+/// The only allocation is the traversal stack, which holds the not-yet-visited siblings of each node
+/// on the path to the current node.
+///
+/// ```rust
+/// # use recursion::*;
+/// # enum ExprFrame<A> {
+/// #     Add(A, A),
+/// #     LiteralInt(i64),
+/// # }
+/// # impl MappableFrame for ExprFrame<PartiallyApplied> {
+/// #     type Frame<X> = ExprFrame<X>;
+/// #     fn map_frame<A, B>(input: Self::Frame<A>, mut f: impl FnMut(A) -> B) -> Self::Frame<B> {
+/// #         match input {
+/// #             ExprFrame::Add(a, b) => ExprFrame::Add(f(a), f(b)),
+/// #             ExprFrame::LiteralInt(x) => ExprFrame::LiteralInt(x),
+/// #         }
+/// #     }
+/// # }
+/// #[derive(Debug, PartialEq)]
+/// enum Expr {
+///     Add(Box<Expr>, Box<Expr>),
+///     LiteralInt(i64),
+/// }
+///
+/// impl<'a> Collapsible for &'a mut Expr {
+///     type FrameToken = ExprFrame<PartiallyApplied>;
+///
+///     fn into_frame(self) -> ExprFrame<&'a mut Expr> {
+///         match self {
+///             Expr::Add(a, b) => ExprFrame::Add(a, b),
+///             Expr::LiteralInt(x) => ExprFrame::LiteralInt(*x),
+///         }
+///     }
+/// }
+///
+/// let lit = |x| Box::new(Expr::LiteralInt(x));
+/// let mut expr = Expr::Add(lit(-1), Box::new(Expr::Add(lit(2), lit(-3))));
+///
+/// for_each_node_mut(&mut expr, |node| {
+///     if let Expr::LiteralInt(x) = node {
+///         *x = x.abs();
+///     }
+/// });
+///
+/// assert_eq!(expr, Expr::Add(lit(1), Box::new(Expr::Add(lit(2), lit(3)))));
+/// ```
+pub fn for_each_node_mut<T, F>(root: &mut T, mut f: impl FnMut(&mut T))
+where
+    for<'a> &'a mut T: Collapsible<FrameToken = F>,
+    F: MappableFrame,
+{
+    let mut stack = Vec::new();
+    let mut children = Vec::new();
+    stack.push(root);
+
+    while let Some(node) = stack.pop() {
+        f(node);
+        F::map_frame(node.into_frame(), |child| children.push(child));
+        stack.extend(children.drain(..).rev());
+    }
+}